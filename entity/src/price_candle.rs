@@ -9,17 +9,21 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub token: String,
-    #[sea_orm(column_type = "Float")]
-    pub open: f32,
-    #[sea_orm(column_type = "Float")]
-    pub high: f32,
-    #[sea_orm(column_type = "Float")]
-    pub low: f32,
-    #[sea_orm(column_type = "Float")]
-    pub close: f32,
+    #[sea_orm(column_type = "Decimal(None)")]
+    pub open: Decimal,
+    #[sea_orm(column_type = "Decimal(None)")]
+    pub high: Decimal,
+    #[sea_orm(column_type = "Decimal(None)")]
+    pub low: Decimal,
+    #[sea_orm(column_type = "Decimal(None)")]
+    pub close: Decimal,
     pub timestamp: i64,
     pub period: String,
     pub chain_id: i64,
+    pub aggregation_strategy: Option<String>,
+    pub price_sources: Option<String>,
+    #[sea_orm(column_type = "Decimal(None)", nullable)]
+    pub spread: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
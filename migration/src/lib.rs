@@ -1,6 +1,11 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20250323_113734_create_pricecandle_table;
+mod m20261018_101500_add_pricecandle_price_source;
+mod m20261018_143000_add_pricecandle_unique_index;
+mod m20261018_160000_add_pricecandle_query_index;
+mod m20261018_180000_pricecandle_prices_to_numeric;
+mod m20261018_200000_add_pricecandle_spread;
 
 pub struct Migrator;
 
@@ -9,6 +14,11 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(m20250323_113734_create_pricecandle_table::Migration),
+            Box::new(m20261018_101500_add_pricecandle_price_source::Migration),
+            Box::new(m20261018_143000_add_pricecandle_unique_index::Migration),
+            Box::new(m20261018_160000_add_pricecandle_query_index::Migration),
+            Box::new(m20261018_180000_pricecandle_prices_to_numeric::Migration),
+            Box::new(m20261018_200000_add_pricecandle_spread::Migration),
         ]
     }
 }
@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PriceCandle::Table)
+                    .add_column(string_null(PriceCandle::AggregationStrategy)) // Strategy of the latest close
+                    .add_column(string_null(PriceCandle::PriceSources)) // Comma-separated sources of the latest close
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PriceCandle::Table)
+                    .drop_column(PriceCandle::AggregationStrategy)
+                    .drop_column(PriceCandle::PriceSources)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PriceCandle {
+    Table,
+    AggregationStrategy,
+    PriceSources,
+}
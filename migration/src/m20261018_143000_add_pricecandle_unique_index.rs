@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+const UNIQUE_INDEX: &str = "idx_price_candle_chain_token_period_timestamp";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Merge duplicate candles into the newest row of each group: first
+        // open, highest high, lowest low and the newest close.
+        db.execute_unprepared(
+            r#"
+            UPDATE price_candle AS kept
+            SET open = grp.open, high = grp.high, low = grp.low
+            FROM (
+                SELECT MAX(id) AS id,
+                       (ARRAY_AGG(open ORDER BY id))[1] AS open,
+                       MAX(high) AS high,
+                       MIN(low) AS low
+                FROM price_candle
+                GROUP BY chain_id, token, period, timestamp
+                HAVING COUNT(*) > 1
+            ) AS grp
+            WHERE kept.id = grp.id
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            DELETE FROM price_candle AS dup
+            USING price_candle AS kept
+            WHERE dup.chain_id = kept.chain_id
+              AND dup.token = kept.token
+              AND dup.period = kept.period
+              AND dup.timestamp = kept.timestamp
+              AND dup.id < kept.id
+            "#,
+        )
+        .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(UNIQUE_INDEX)
+                    .table(PriceCandle::Table)
+                    .col(PriceCandle::ChainId)
+                    .col(PriceCandle::Token)
+                    .col(PriceCandle::Period)
+                    .col(PriceCandle::Timestamp)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(UNIQUE_INDEX)
+                    .table(PriceCandle::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PriceCandle {
+    Table,
+    Token,
+    Timestamp,
+    Period,
+    ChainId,
+}
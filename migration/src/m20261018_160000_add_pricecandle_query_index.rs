@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+const QUERY_INDEX: &str = "idx_price_candle_chain_period_timestamp";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Serves the "latest candles of a period across tokens" reads; lookups
+        // by token go through the unique index.
+        manager
+            .create_index(
+                Index::create()
+                    .name(QUERY_INDEX)
+                    .table(PriceCandle::Table)
+                    .col(PriceCandle::ChainId)
+                    .col(PriceCandle::Period)
+                    .col((PriceCandle::Timestamp, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(QUERY_INDEX)
+                    .table(PriceCandle::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PriceCandle {
+    Table,
+    Timestamp,
+    Period,
+    ChainId,
+}
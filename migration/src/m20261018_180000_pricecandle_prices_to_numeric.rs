@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Unconstrained NUMERIC keeps every digit the writer stores; existing
+        // REAL values are converted as they are.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PriceCandle::Table)
+                    .modify_column(ColumnDef::new(PriceCandle::Open).decimal().not_null())
+                    .modify_column(ColumnDef::new(PriceCandle::High).decimal().not_null())
+                    .modify_column(ColumnDef::new(PriceCandle::Low).decimal().not_null())
+                    .modify_column(ColumnDef::new(PriceCandle::Close).decimal().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PriceCandle::Table)
+                    .modify_column(ColumnDef::new(PriceCandle::Open).float().not_null())
+                    .modify_column(ColumnDef::new(PriceCandle::High).float().not_null())
+                    .modify_column(ColumnDef::new(PriceCandle::Low).float().not_null())
+                    .modify_column(ColumnDef::new(PriceCandle::Close).float().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PriceCandle {
+    Table,
+    Open,
+    High,
+    Low,
+    Close,
+}
@@ -1,40 +1,17 @@
-
-
-// mod db;
-// mod pricecandlemodel;
-// mod schema;
-
-// #[path = "../../../utils/helpersutils.rs"]
-// mod helpersutils;
-
-// #[path = "../config/bondconfig.rs"]
-// mod bondconfig;
-
-
-// use diesel::prelude::*;
-// use db::establish_connection;
-// use pricecandlemodel::PriceCandle;
-// use bondconfig::{BONDS_PERIOD_ID_MAPPING ,BONDS_SYMBOL_TO_ID_MAPPING };
-
-
-
-// async fn main(){
-//     let connection = &mut establish_connection();
-//     println!("Connected to the database!");
-//     for (asset , assetvalue ) in BONDS_SYMBOL_TO_ID_MAPPING{
-//         for (period , periodvalue) in BONDS_PERIOD_ID_MAPPING{
-//             println!("trying {:?}" , assetvalue);
-//             let  response = match investing(assetvalue.to_string() , "P5Y" , periodvalue.to_string()).await {
-//                 Ok(data)=>data,
-//                 Err(e)=>{
-//                     eprintln!("Error to fetch investing : {}",e);
-//                 }
-//             };
-
-
-
-//         }
-//     }
-
-// }
-
+use sea_orm::DatabaseConnection;
+
+use crate::assets::bonds::config::bondconfig::{BONDS_PERIOD_ID_MAPPING, BONDS_SYMBOL_TO_ID_MAPPING};
+use crate::configs::registryconfig::AssetClass;
+use crate::utils::investingutils::{run_investing_feed, InvestingFeed};
+use crate::utils::latestpricesutils::LatestPriceCache;
+
+// Backfills bond yields once, then refreshes the recent bars on the bond
+// class's update interval.
+pub async fn run_bond_feed(db: &DatabaseConnection, latest_prices: LatestPriceCache) {
+    let feed = InvestingFeed {
+        asset_class: AssetClass::Bond,
+        symbol_ids: &BONDS_SYMBOL_TO_ID_MAPPING,
+        intervals: &BONDS_PERIOD_ID_MAPPING,
+    };
+    run_investing_feed(db, latest_prices, &feed).await;
+}
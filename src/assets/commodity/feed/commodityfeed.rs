@@ -0,0 +1,18 @@
+use sea_orm::DatabaseConnection;
+
+use crate::assets::commodity::config::commodityconfig::{PERIOD_ID_MAPPING, SYMBOL_TO_ID_MAPPING};
+use crate::configs::registryconfig::AssetClass;
+use crate::utils::investingutils::{run_investing_feed, InvestingFeed};
+use crate::utils::latestpricesutils::LatestPriceCache;
+
+// Backfills commodity prices once, then refreshes the recent bars on the
+// commodity class's update interval. Closed-market and no-trade bars are not
+// stored.
+pub async fn run_commodity_feed(db: &DatabaseConnection, latest_prices: LatestPriceCache) {
+    let feed = InvestingFeed {
+        asset_class: AssetClass::Commodity,
+        symbol_ids: &SYMBOL_TO_ID_MAPPING,
+        intervals: &PERIOD_ID_MAPPING,
+    };
+    run_investing_feed(db, latest_prices, &feed).await;
+}
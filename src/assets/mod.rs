@@ -1,3 +1,3 @@
-
 pub mod bonds;
-pub mod commodity;
\ No newline at end of file
+pub mod commodity;
+pub mod realestate;
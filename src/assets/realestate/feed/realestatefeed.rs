@@ -0,0 +1,262 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use sea_orm::DatabaseConnection;
+
+use crate::configs::envconfig::{ENV, SERVED_CHAIN_IDS};
+use crate::configs::registryconfig::{AssetClass, AssetRecord, REGISTRY};
+use crate::utils::candlesutils::{upsert_candle_bars, CandleBar};
+use crate::utils::helpersutils::{sleep_ms, PERIOD_MAP, SYMBOL_TO_ADDRESS_MAPPING};
+use crate::utils::latestpricesutils::{unix_now, LatestPrice, LatestPriceCache};
+use crate::utils::pricesutils::{fetch_all_parcl_ids, fetch_parcl_info};
+use crate::utils::responseinterfaceutils::Info;
+
+const REALESTATE_PRICE_SOURCE: &str = "parcl";
+
+// Parcl market id of every real-estate asset listed on a served chain.
+// Assets without a pinned `parcl_id` are logged once and not priced.
+fn pinned_parcl_ids() -> BTreeMap<String, i64> {
+    let mut parcl_ids = BTreeMap::new();
+    for asset in real_estate_assets() {
+        match asset.parcl_id {
+            Some(parcl_id) => {
+                parcl_ids.insert(asset.symbol.clone(), parcl_id);
+            }
+            None => eprintln!("No parcl_id set for {}; it is not priced", asset.symbol),
+        }
+    }
+    parcl_ids
+}
+
+fn real_estate_assets() -> impl Iterator<Item = &'static AssetRecord> {
+    SERVED_CHAIN_IDS.iter().flat_map(|chain_id| {
+        REGISTRY
+            .listed_assets(*chain_id)
+            .filter(|asset| asset.asset_class == AssetClass::RealEstate)
+    })
+}
+
+// Offline lookup of the ids to pin: matches the `parcl_name` of every
+// real-estate asset without a `parcl_id` against the names of Parcl's
+// markets. Parcl only lists market ids, so markets are fetched one at a time
+// until every name is found. Returns symbol -> Parcl id.
+pub async fn resolve_parcl_names(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> Result<BTreeMap<String, i64>, Box<dyn std::error::Error>> {
+    // Lower-cased name -> symbol
+    let mut wanted_names: HashMap<String, String> = real_estate_assets()
+        .filter(|asset| asset.parcl_id.is_none())
+        .filter_map(|asset| {
+            let name = asset.parcl_name.as_ref()?;
+            Some((name.to_lowercase(), asset.symbol.clone()))
+        })
+        .collect();
+
+    let all_ids = fetch_all_parcl_ids(client, base_url).await?;
+    let mut resolved = BTreeMap::new();
+    for parcl_id in all_ids {
+        if wanted_names.is_empty() {
+            break;
+        }
+        let name = match fetch_parcl_info(client, base_url, &parcl_id.to_string()).await {
+            Ok(info) => info.name.to_lowercase(),
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+        if let Some(symbol) = wanted_names.remove(&name) {
+            resolved.insert(symbol, parcl_id);
+        }
+    }
+
+    for (name, symbol) in &wanted_names {
+        eprintln!("No Parcl market named {:?} for {}", name, symbol);
+    }
+    Ok(resolved)
+}
+
+// One 1d candle per `price_history` date plus today's bar at the current
+// price. Each candle opens at the previous day's close.
+fn parcl_daily_bars(info: &Info, today: i64) -> Vec<CandleBar> {
+    let mut prices: BTreeMap<i64, Decimal> = BTreeMap::new();
+    for point in &info.price_history {
+        let date = point.date.get(..10).unwrap_or(&point.date);
+        let (Ok(date), Some(price)) = (
+            NaiveDate::parse_from_str(date, "%Y-%m-%d"),
+            Decimal::from_f64(point.price),
+        ) else {
+            continue;
+        };
+        if let Some(midnight) = date.and_hms_opt(0, 0, 0) {
+            prices.insert(midnight.and_utc().timestamp(), price.normalize());
+        }
+    }
+    if let Some(current_price) = Decimal::from_f64(info.current_price) {
+        prices.insert(today, current_price.normalize());
+    }
+
+    let mut previous_close: Option<Decimal> = None;
+    prices
+        .into_iter()
+        .filter(|(_, price)| *price > Decimal::ZERO)
+        .map(|(timestamp, close)| {
+            let open = previous_close.unwrap_or(close);
+            previous_close = Some(close);
+            CandleBar {
+                timestamp,
+                open,
+                high: open.max(close),
+                low: open.min(close),
+                close,
+            }
+        })
+        .collect()
+}
+
+// Backfills the Parcl price history of every real-estate asset into candles
+// of the class's latest period (1d) once, then appends the current price on
+// the class's update interval (daily).
+pub async fn run_realestate_feed(db: &DatabaseConnection, latest_prices: LatestPriceCache) {
+    let client = reqwest::Client::new();
+    let class_config = REGISTRY.class_config(AssetClass::RealEstate);
+    let period = class_config.latest_period.as_str();
+    let day = PERIOD_MAP[period] as i64;
+    let parcl_ids = pinned_parcl_ids();
+    let mut backfilled: Vec<String> = Vec::new();
+
+    loop {
+        let today = unix_now() as i64 / day * day;
+
+        for (symbol, parcl_id) in &parcl_ids {
+            let parcl_id = parcl_id.to_string();
+            let info = match fetch_parcl_info(&client, &ENV.PARCL_BASE_URL, &parcl_id).await {
+                Ok(info) => info,
+                Err(e) => {
+                    eprintln!("Error fetching Parcl market {} for {}: {}", parcl_id, symbol, e);
+                    continue;
+                }
+            };
+            let mut bars = parcl_daily_bars(&info, today);
+            // After the backfill only yesterday (which Parcl may have revised)
+            // and today are written.
+            let backfill = !backfilled.contains(symbol);
+            if !backfill {
+                bars.drain(..bars.len().saturating_sub(2));
+            }
+            let Some(last_bar) = bars.last() else {
+                continue;
+            };
+
+            let mut stored = true;
+            for chain_id in SERVED_CHAIN_IDS.iter() {
+                let Some(address) = SYMBOL_TO_ADDRESS_MAPPING
+                    .get(chain_id)
+                    .and_then(|addresses| addresses.get(symbol))
+                else {
+                    continue;
+                };
+                if let Err(e) = upsert_candle_bars(
+                    db,
+                    *chain_id,
+                    address,
+                    period,
+                    &bars,
+                    REALESTATE_PRICE_SOURCE,
+                )
+                .await
+                {
+                    eprintln!("Error storing {} candles: {}", symbol, e);
+                    stored = false;
+                    continue;
+                }
+                latest_prices.update(
+                    *chain_id,
+                    symbol,
+                    LatestPrice {
+                        price: last_bar.close,
+                        spread: None,
+                        updated_at: unix_now(),
+                    },
+                );
+            }
+            if backfill && stored {
+                backfilled.push(symbol.clone());
+            }
+        }
+
+        sleep_ms(class_config.update_interval_ms).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn info_fixture(parcl_id: i64, name: &str) -> serde_json::Value {
+        let body = std::fs::read_to_string("tests/fixtures/parcl/info.json")
+            .expect("Failed to read fixture");
+        let mut response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        response["info"]["parcl_id"] = parcl_id.into();
+        response["info"]["name"] = name.into();
+        response
+    }
+
+    #[test]
+    fn daily_bars_open_at_the_previous_close() {
+        let response = info_fixture(2900187, "Charlotte");
+        let info: Info = serde_json::from_value(response["info"].clone()).unwrap();
+        // 2026-10-18
+        let bars = parcl_daily_bars(&info, 1792281600);
+
+        let timestamps: Vec<i64> = bars.iter().map(|bar| bar.timestamp).collect();
+        // The zero price and the bad date are skipped.
+        assert_eq!(timestamps, vec![1791936000, 1792022400, 1792108800, 1792281600]);
+        let decimal = |value: &str| value.parse::<Decimal>().unwrap();
+        assert_eq!(
+            (bars[0].open, bars[0].close),
+            (decimal("310.5"), decimal("310.5"))
+        );
+        assert_eq!(
+            (bars[2].open, bars[2].high, bars[2].low, bars[2].close),
+            (decimal("311.25"), decimal("311.25"), decimal("309.8"), decimal("309.8"))
+        );
+        assert_eq!((bars[3].open, bars[3].close), (decimal("309.8"), decimal("312.42")));
+    }
+
+    #[tokio::test]
+    async fn resolves_names_until_every_market_is_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/metadata/parcl-ids"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"ids": [101, 102, 103]})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        for (parcl_id, name) in [(101, "Charlotte"), (102, "Denver"), (103, "Nowhere")] {
+            Mock::given(method("GET"))
+                .and(path("/v1/real-estate-data/parcl-info"))
+                .and(query_param("id", parcl_id.to_string()))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(info_fixture(parcl_id, name)),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = reqwest::Client::new();
+        let resolved = resolve_parcl_names(&client, &server.uri()).await.unwrap();
+        assert_eq!(resolved.get("CLT"), Some(&101));
+        assert_eq!(resolved.get("DEN"), Some(&102));
+        assert_eq!(resolved.len(), 2);
+    }
+}
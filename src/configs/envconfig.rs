@@ -5,13 +5,86 @@ use serde::{Deserialize, Serialize};
 use dotenv::dotenv;
 use std::env;
 
+use crate::configs::registryconfig::REGISTRY;
+use crate::utils::aggregationutils::AggregationStrategy;
+
 
 #[derive(Serialize, Deserialize, Debug)]
 #[allow(non_snake_case)]
 pub struct EnvConfig {
-    pub NETWORK:String,
+    pub NETWORKS: Vec<String>,
     pub MAX_PRICE_INTERVAL: u32,
     pub PORT: u16,
+    pub REGISTRY_PATH: String,
+    #[serde(default)]
+    pub EXCHANGE_BASE_URLS: HashMap<String, String>,
+    #[serde(default)]
+    pub AGGREGATION_STRATEGY: AggregationStrategy,
+    #[serde(default)]
+    pub SOURCE_WEIGHTS: HashMap<String, f64>,
+    #[serde(default = "default_hermes_base_url")]
+    pub HERMES_BASE_URL: String,
+    #[serde(default)]
+    pub PYTH_FEED_MODE: PythFeedMode,
+    #[serde(default = "default_max_conf_ratio")]
+    pub MAX_CONF_RATIO: f64,
+    #[serde(default)]
+    pub USE_EMA_FALLBACK: bool,
+    #[serde(default)]
+    pub CANDLE_OPEN_MODE: CandleOpenMode,
+    #[serde(default = "default_investing_base_url")]
+    pub INVESTING_BASE_URL: String,
+    #[serde(default = "default_parcl_base_url")]
+    pub PARCL_BASE_URL: String,
+    #[serde(default = "default_calendar_path")]
+    pub CALENDAR_PATH: String,
+}
+
+// `stream` consumes the Hermes SSE endpoint; `poll` fetches the latest prices
+// on the submitter's fetch interval and is kept as a fallback.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PythFeedMode {
+    #[default]
+    Stream,
+    Poll,
+}
+
+// How a new candle's open is chosen. Only `smoothed` touches a candle after
+// its bucket has closed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleOpenMode {
+    // Close of the directly preceding candle, or the first price if there is
+    // none (e.g. after a gap).
+    #[default]
+    PreviousClose,
+    // First price observed in the bucket.
+    FirstPrice,
+    // Legacy behaviour: open halfway between the first price and the previous
+    // close, and rewrite that close to the same value.
+    Smoothed,
+}
+
+fn default_hermes_base_url() -> String {
+    "https://hermes.pyth.network".to_string()
+}
+
+fn default_investing_base_url() -> String {
+    "https://api.investing.com".to_string()
+}
+
+fn default_parcl_base_url() -> String {
+    "https://parcl-api.com".to_string()
+}
+
+fn default_calendar_path() -> String {
+    "src/calendar.json".to_string()
+}
+
+// Pyth updates whose conf / price is above this ratio are rejected.
+fn default_max_conf_ratio() -> f64 {
+    0.02
 }
 
 pub static ENV: Lazy<EnvConfig> = Lazy::new(|| {
@@ -23,12 +96,30 @@ pub static ENV: Lazy<EnvConfig> = Lazy::new(|| {
     serde_json::from_str(&env_content).expect("Failed to parse env.json")
 });
 
-#[allow(unused)]
 pub static CHAINID_MAP: Lazy<HashMap<String, i64>> = Lazy::new(|| {
-    let mut map = HashMap::new();
-    map.insert("arbitrum_sepolia".to_string(), 421614);
-    map.insert("bitlayer_testnet".to_string(), 200810);
-    map.insert("move_testnet".to_string(), 336);
-    map.insert("bera_testnet".to_string(), 80084);
-    map
+    REGISTRY
+        .networks
+        .iter()
+        .map(|(network, network_assets)| (network.to_string(), network_assets.chain_id))
+        .collect()
 });
+
+// Chain ids of the networks this process serves, in `NETWORKS` order. The
+// first one is used when a request does not pass a `chainId`.
+pub static SERVED_CHAIN_IDS: Lazy<Vec<i64>> = Lazy::new(|| {
+    ENV.NETWORKS
+        .iter()
+        .map(|network| {
+            *CHAINID_MAP
+                .get(network)
+                .unwrap_or_else(|| panic!("Network {} not found in asset registry", network))
+        })
+        .collect()
+});
+
+pub fn resolve_chain_id(chain_id: Option<i64>) -> Option<i64> {
+    match chain_id {
+        Some(id) => SERVED_CHAIN_IDS.contains(&id).then_some(id),
+        None => SERVED_CHAIN_IDS.first().copied(),
+    }
+}
@@ -1,2 +1,3 @@
 pub mod envconfig;
 pub mod config;
+pub mod registryconfig;
@@ -0,0 +1,237 @@
+use std::{collections::HashMap, fs};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::configs::envconfig::ENV;
+
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetClass {
+    Crypto,
+    Forex,
+    RealEstate,
+    Commodity,
+    Bond,
+}
+
+impl AssetClass {
+    pub const ALL: [AssetClass; 5] = [
+        AssetClass::Crypto,
+        AssetClass::Forex,
+        AssetClass::RealEstate,
+        AssetClass::Commodity,
+        AssetClass::Bond,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetClass::Crypto => "crypto",
+            AssetClass::Forex => "forex",
+            AssetClass::RealEstate => "real_estate",
+            AssetClass::Commodity => "commodity",
+            AssetClass::Bond => "bond",
+        }
+    }
+
+    // Used for classes the registry's `asset_classes` leaves out.
+    pub fn default_config(&self) -> AssetClassConfig {
+        let (feed, update_interval_ms, staleness_secs, trading_hours, latest_period) = match self {
+            AssetClass::Crypto => (PriceFeedKind::Submitter, 5_000, 300, "always", "1m"),
+            AssetClass::Forex => (PriceFeedKind::None, 5_000, 300, "weekdays", "1m"),
+            AssetClass::Commodity => (PriceFeedKind::Investing, 60_000, 900, "weekdays", "1m"),
+            AssetClass::Bond => (PriceFeedKind::Investing, 60_000, 900, "weekdays", "1m"),
+            AssetClass::RealEstate => {
+                (PriceFeedKind::Parcl, 24 * 60 * 60 * 1000, 2 * 24 * 60 * 60, "always", "1d")
+            }
+        };
+        AssetClassConfig {
+            feed,
+            update_interval_ms,
+            staleness_secs,
+            trading_hours: trading_hours.to_string(),
+            latest_period: latest_period.to_string(),
+        }
+    }
+}
+
+// The job that writes a class's prices.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceFeedKind {
+    // Aggregated from Pyth and exchange tickers by the price submitter.
+    Submitter,
+    // Investing chart bars (bonds, commodities).
+    Investing,
+    // Parcl daily prices (real estate).
+    Parcl,
+    // Not priced.
+    None,
+}
+
+// How the assets of one class are priced and served.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AssetClassConfig {
+    pub feed: PriceFeedKind,
+    // How often the class's feed fetches new prices.
+    pub update_interval_ms: u64,
+    // A ticker price older than this is reported as stale.
+    pub staleness_secs: u64,
+    // Name of the trading calendar schedule the class trades on.
+    pub trading_hours: String,
+    // Candle period the latest stored price is read from.
+    pub latest_period: String,
+}
+
+// A price computed from other assets' aggregated prices instead of being
+// fetched, e.g. WBTC as an alias of BTC or WSTETH as ETH x the stETH rate.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DerivedPrice {
+    Alias { of: String },
+    Ratio { numerator: String, denominator: String },
+    Product { factors: Vec<String> },
+}
+
+impl DerivedPrice {
+    pub fn inputs(&self) -> Vec<&str> {
+        match self {
+            DerivedPrice::Alias { of } => vec![of.as_str()],
+            DerivedPrice::Ratio { numerator, denominator } => {
+                vec![numerator.as_str(), denominator.as_str()]
+            }
+            DerivedPrice::Product { factors } => factors.iter().map(|f| f.as_str()).collect(),
+        }
+    }
+}
+
+// How the min/max band served in /prices/tickers is derived from the price.
+// Every policy yields a half-width that is subtracted from and added to the
+// price; `min_bps` puts a floor under the data-driven ones.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpreadPolicy {
+    // min_price == max_price.
+    #[default]
+    None,
+    // Fixed spread of `bps` basis points on each side.
+    Bps { bps: f64 },
+    // Pyth's confidence interval times `multiplier`.
+    PythConf {
+        #[serde(default = "default_spread_multiplier")]
+        multiplier: f64,
+        #[serde(default)]
+        min_bps: f64,
+    },
+    // Half the range between the lowest and highest source price, times
+    // `multiplier`.
+    SourceDispersion {
+        #[serde(default = "default_spread_multiplier")]
+        multiplier: f64,
+        #[serde(default)]
+        min_bps: f64,
+    },
+}
+
+fn default_spread_multiplier() -> f64 {
+    1.0
+}
+
+impl SpreadPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpreadPolicy::None => "none",
+            SpreadPolicy::Bps { .. } => "bps",
+            SpreadPolicy::PythConf { .. } => "pyth_conf",
+            SpreadPolicy::SourceDispersion { .. } => "source_dispersion",
+        }
+    }
+}
+
+// One asset on one network. Assets without an address are price-only feeds
+// (e.g. ETH, used to price WETH) and are not listed as supported tokens.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AssetRecord {
+    pub symbol: String,
+    pub address: Option<String>,
+    pub decimals: Option<u64>,
+    pub pyth_id: Option<String>,
+    #[serde(default)]
+    pub exchange_tickers: HashMap<String, String>,
+    pub asset_class: AssetClass,
+    pub derived: Option<DerivedPrice>,
+    #[serde(default)]
+    pub spread: SpreadPolicy,
+    // Parcl market of a real-estate asset, which the feed prices by
+    // `parcl_id`. `parcl_name` is only read by `resolve-parcl-ids`, which
+    // looks up the ids to pin.
+    pub parcl_id: Option<i64>,
+    pub parcl_name: Option<String>,
+    // Trading calendar schedule overriding the asset class's trading_hours.
+    pub trading_calendar: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NetworkAssets {
+    pub chain_id: i64,
+    pub assets: Vec<AssetRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssetRegistry {
+    pub networks: HashMap<String, NetworkAssets>,
+    // Filled in with each class's default_config where missing.
+    #[serde(default)]
+    pub asset_classes: HashMap<AssetClass, AssetClassConfig>,
+}
+
+impl AssetRegistry {
+    pub fn class_config(&self, asset_class: AssetClass) -> &AssetClassConfig {
+        &self.asset_classes[&asset_class]
+    }
+
+    // Shortest update interval among the classes `feed` prices.
+    pub fn feed_interval_ms(&self, feed: PriceFeedKind) -> Option<u64> {
+        self.asset_classes
+            .values()
+            .filter(|config| config.feed == feed)
+            .map(|config| config.update_interval_ms)
+            .min()
+    }
+
+    pub fn chain_ids(&self) -> impl Iterator<Item = i64> + '_ {
+        self.networks.values().map(|network| network.chain_id)
+    }
+
+    pub fn assets(&self, chain_id: i64) -> &[AssetRecord] {
+        match self.networks.values().find(|network| network.chain_id == chain_id) {
+            Some(network_assets) => &network_assets.assets,
+            None => &[],
+        }
+    }
+
+    pub fn asset(&self, chain_id: i64, symbol: &str) -> Option<&AssetRecord> {
+        self.assets(chain_id).iter().find(|asset| asset.symbol == symbol)
+    }
+
+    // Assets that have a deployed token on the network, in registry order.
+    pub fn listed_assets(&self, chain_id: i64) -> impl Iterator<Item = &AssetRecord> {
+        self.assets(chain_id)
+            .iter()
+            .filter(|asset| asset.address.is_some())
+    }
+}
+
+pub static REGISTRY: Lazy<AssetRegistry> = Lazy::new(|| {
+    let registry_content =
+        fs::read_to_string(&ENV.REGISTRY_PATH).expect("Failed to read asset registry");
+    let mut registry: AssetRegistry =
+        serde_json::from_str(&registry_content).expect("Failed to parse asset registry");
+    for asset_class in AssetClass::ALL {
+        registry
+            .asset_classes
+            .entry(asset_class)
+            .or_insert_with(|| asset_class.default_config());
+    }
+    registry
+});
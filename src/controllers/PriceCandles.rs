@@ -5,20 +5,17 @@ use crate::utils::helpersutils::{
     SYMBOL_TO_ADDRESS_MAPPING
 };
 
-use crate::configs::envconfig::{CHAINID_MAP , ENV};
+use crate::configs::envconfig::resolve_chain_id;
+use crate::utils::candlesutils::{
+    decode_cursor, encode_cursor, get_candles, CandleOrder, CandleRange
+};
 use crate::DbConnection;
 use rocket::{get, State};
-use rocket::response::status::BadRequest;
-use sea_orm::entity::prelude::*;
-use sea_orm::{EntityTrait, QueryFilter};
-use sea_orm::*;
+use crate::utils::apierrorutils::ApiError;
+use sea_orm::JsonValue;
 use serde_json::json;
 use rocket::serde::{json::Json, Deserialize};
-use entities::{prelude::*, *};
-
 
-#[path = "../../entity/src/mod.rs"]
-mod entities;
 
 
 
@@ -31,13 +28,19 @@ pub struct ParamData{
     pub limit : String
 }
 
-#[get("/candles?<period>&<token_symbol>&<limit>")] 
+#[allow(non_snake_case, clippy::too_many_arguments)]
+#[get("/candles?<period>&<token_symbol>&<limit>&<chainId>&<from>&<to>&<order>&<cursor>")] 
 pub async fn get_price_candles (
     period: String,
     token_symbol: String,
     limit: Option<u32>,
+    chainId: Option<i64>,
+    from: Option<i64>,
+    to: Option<i64>,
+    order: Option<String>,
+    cursor: Option<String>,
     db: &State<DbConnection>
-) -> Result<Json<JsonValue>, BadRequest<Json<JsonValue>>> {
+) -> Result<Json<JsonValue>, ApiError> {
     // dotenv().ok();
     // let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     // let db = match Database::connect(&db_url).await {
@@ -49,33 +52,38 @@ pub async fn get_price_candles (
     let supportedperiods = SUPPORTED_PERIODS.clone();
 
     if !supportedperiods.contains(&period.as_str()){
-        return Err(BadRequest(Json(json!({
-            "error": format!("Unsupported period. Supported: {:?}", supportedperiods)
-        }))))
+        return Err(ApiError::UnsupportedPeriod(format!(
+            "Unsupported period. Supported: {:?}", supportedperiods
+        )))
     }
 
+    let chainid = match resolve_chain_id(chainId){
+        Some(data)=>data,
+        None=> {
+            return Err(ApiError::UnsupportedChain);
+        }
+    };
+
     // let tokenSymbol = param.tokenSymbol.as_str();
-    let supported_tokens = match SUPPORTED_TOKENS.get(&ENV.NETWORK){
+    let supported_tokens = match SUPPORTED_TOKENS.get(&chainid){
         Some(data)=>data.clone(),
         None=> {
-            return Err(BadRequest(Json(json!({
-                "error": "Could not get supported tokens"
-            }))));
+            return Err(ApiError::Internal("Could not get supported tokens".to_string()));
         }
     };
 
     if !supported_tokens.contains(&token_symbol.as_str()){
-        return Err(BadRequest(Json(json!({
-            "error": format!("Unsupported token. Supported: {:?}", supported_tokens)
-        }))));
+        return Err(ApiError::UnsupportedToken(format!(
+            "Unsupported token. Supported: {:?}", supported_tokens
+        )));
     }
 
-    let token_address = match SYMBOL_TO_ADDRESS_MAPPING.get(&token_symbol){
+    let token_address = match SYMBOL_TO_ADDRESS_MAPPING
+        .get(&chainid)
+        .and_then(|addresses| addresses.get(&token_symbol)){
         Some(data)=>data,
         None=> {
-            return Err(BadRequest(Json(json!({
-                "error": "Token address not found"
-            }))));
+            return Err(ApiError::NotFound("Token address not found".to_string()));
         }
     };
 
@@ -91,44 +99,220 @@ pub async fn get_price_candles (
     //     limit = MAX_LIMIT
     // }
 
-    let chainid = match CHAINID_MAP.get(&ENV.NETWORK){
-        Some(data)=>data,
-        None=> {
-            return Err(BadRequest(Json(json!({
-                "error": "Could not get chain ID"
-            }))));
+    let order = match order.as_deref() {
+        None | Some("desc") => CandleOrder::NewestFirst,
+        Some("asc") => CandleOrder::OldestFirst,
+        Some(_) => {
+            return Err(ApiError::InvalidParameter(
+                "Unsupported order. Supported: [\"asc\", \"desc\"]".to_string()
+            ));
+        }
+    };
+
+    let mut range = CandleRange { from, to, ..Default::default() };
+
+    // A cursor continues past the last candle of the previous page, in the
+    // same order
+    if let Some(cursor) = cursor {
+        match decode_cursor(&cursor, &token_symbol, &period, order) {
+            Some(timestamp) => range = order.page_after(range, timestamp),
+            None => {
+                return Err(ApiError::InvalidParameter("Invalid cursor".to_string()));
+            }
+        }
+    }
+
+    // Served from stored candles of the period, or resampled from finer ones
+    let candles = get_candles(
+        &db.0, chainid, token_address, &period, &range, order, limit as u64
+    ).await?;
+
+    // A full page may have more candles left
+    let next_cursor = match candles.last() {
+        Some(last) if limit > 0 && candles.len() == limit as usize => {
+            Some(encode_cursor(&token_symbol, &period, order, last.timestamp))
         }
+        _ => None,
     };
 
-    // let candlesData = await 
-    let candles_data = PriceCandle::find()
-      .filter(price_candle::Column::Token.eq(token_address))
-      .filter(price_candle::Column::Period.eq("1d"))
-      .filter(price_candle::Column::ChainId.eq(chainid.clone()))
-      .order_by_desc(price_candle::Column::Timestamp)
-      .limit(limit as u64)
-      .all(&db.0)
-      .await;
-
-    match candles_data {
-        Ok(candle) => {
-                let formatted_candle = candle
-                .iter()
-                .map(|candle| json!([
-                    candle.timestamp,
-                    candle.open,
-                    candle.high,
-                    candle.low,
-                    candle.close
-                ]))
-                .collect::<Vec<_>>();
-                Ok(Json(json!({
-                    "period": period,
-                    "candles": formatted_candle,
-                })))
-            },
-        Err(e) => Err(BadRequest(Json(json!({
-            "error": format!("DB error: {:?}", e)
-        }))))
+    let formatted_candle = candles
+        .iter()
+        .map(|candle| json!([
+            candle.timestamp,
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close
+        ]))
+        .collect::<Vec<_>>();
+
+    Ok(Json(json!({
+        "period": period,
+        "candles": formatted_candle,
+        "next_cursor": next_cursor,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::registryconfig::REGISTRY;
+    use crate::utils::candlesutils::CandleBar;
+    use crate::utils::testdbutils::{clear_token, flat_bar, insert_bars, test_db};
+    use rocket::http::Status;
+    use rocket::local::asynchronous::Client;
+    use rocket::routes;
+    use sea_orm::DatabaseConnection;
+    use serde_json::Value;
+
+    async fn client(db: DatabaseConnection) -> Client {
+        let rocket = rocket::build()
+            .manage(DbConnection(db))
+            .mount("/prices", routes![get_price_candles]);
+        match Client::untracked(rocket).await {
+            Ok(client) => client,
+            Err(e) => panic!("Failed to ignite rocket: {}", e),
+        }
+    }
+
+    async fn get(client: &Client, uri: &str) -> (Status, Value) {
+        let response = client.get(uri.to_string()).dispatch().await;
+        let status = response.status();
+        let body = response.into_json().await.unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    // [timestamp, open, high, low, close] rows of a candles response.
+    fn rows(body: &Value) -> Vec<(i64, String, String, String, String)> {
+        let price = |value: &Value| value.as_str().unwrap_or_default().to_string();
+        body["candles"]
+            .as_array()
+            .expect("No candles in the response")
+            .iter()
+            .map(|row| {
+                let timestamp = row[0].as_i64().unwrap();
+                (timestamp, price(&row[1]), price(&row[2]), price(&row[3]), price(&row[4]))
+            })
+            .collect()
+    }
+
+    fn token_address(symbol: &str) -> (i64, String) {
+        let chain_id = REGISTRY.networks["bitlayer_testnet"].chain_id;
+        (chain_id, SYMBOL_TO_ADDRESS_MAPPING[&chain_id][symbol].clone())
+    }
+
+    // Parameters are validated before the database is queried.
+    #[rocket::async_test]
+    async fn rejects_invalid_parameters() {
+        let client = client(DatabaseConnection::Disconnected).await;
+        let cases = [
+            ("period=2h&token_symbol=XAU", "unsupported_period"),
+            ("period=1h&token_symbol=NOPE", "unsupported_token"),
+            ("period=1h&token_symbol=XAU&chainId=1", "unsupported_chain"),
+            ("period=1h&token_symbol=XAU&order=up", "invalid_parameter"),
+            ("period=1h&token_symbol=XAU&cursor=zz", "invalid_parameter"),
+            ("period=1h&token_symbol=XAU&from=20&to=10", "invalid_parameter"),
+        ];
+
+        for (query, code) in cases {
+            let (status, body) = get(&client, &format!("/prices/candles?{}", query)).await;
+            assert_eq!(status, Status::BadRequest, "{}", query);
+            assert_eq!(body["code"], code, "{}", query);
+        }
+    }
+
+    #[rocket::async_test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn serves_the_stored_candles_of_the_period() {
+        let db = test_db().await;
+        let (chain_id, token) = token_address("XAU");
+        clear_token(&db, chain_id, &token).await;
+        let hour = 3600;
+        let hourly = [flat_bar(hour, "1"), flat_bar(2 * hour, "2"), flat_bar(3 * hour, "3")];
+        insert_bars(&db, chain_id, &token, "1h", &hourly).await;
+        let minutes = [flat_bar(3 * hour, "9"), flat_bar(3 * hour + 60, "9")];
+        insert_bars(&db, chain_id, &token, "1m", &minutes).await;
+        let client = client(db).await;
+
+        let (status, body) = get(&client, "/prices/candles?period=1h&token_symbol=XAU").await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["period"], "1h");
+        let timestamps: Vec<i64> = rows(&body).iter().map(|row| row.0).collect();
+        assert_eq!(timestamps, vec![3 * hour, 2 * hour, hour]);
+        assert_eq!(rows(&body)[0].4, "3");
+        assert!(body["next_cursor"].is_null());
+
+        let (status, body) =
+            get(&client, "/prices/candles?period=1m&token_symbol=XAU&limit=1").await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(rows(&body).len(), 1);
+        assert_eq!(rows(&body)[0].0, 3 * hour + 60);
+        assert!(body["next_cursor"].is_string());
+    }
+
+    #[rocket::async_test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn ascending_pages_start_at_from() {
+        let db = test_db().await;
+        let (chain_id, token) = token_address("XPT");
+        clear_token(&db, chain_id, &token).await;
+        let hour = 3600;
+        let hourly: Vec<_> = (0..6).map(|hours| flat_bar(hours * hour, "1")).collect();
+        insert_bars(&db, chain_id, &token, "1h", &hourly).await;
+        let client = client(db).await;
+        let timestamps = |body: &Value| rows(body).iter().map(|row| row.0).collect::<Vec<_>>();
+
+        let uri = format!(
+            "/prices/candles?period=1h&token_symbol=XPT&order=asc&limit=2&from={}",
+            hour + 1
+        );
+        let (status, body) = get(&client, &uri).await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(timestamps(&body), vec![2 * hour, 3 * hour]);
+
+        // The cursor continues forward, and only with the same order.
+        let cursor = body["next_cursor"].as_str().unwrap().to_string();
+        let (_, body) = get(&client, &format!("{}&cursor={}", uri, cursor)).await;
+        assert_eq!(timestamps(&body), vec![4 * hour, 5 * hour]);
+        let uri = "/prices/candles?period=1h&token_symbol=XPT&limit=2";
+        let (status, _) = get(&client, &format!("{}&cursor={}", uri, cursor)).await;
+        assert_eq!(status, Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn resamples_periods_without_stored_candles() {
+        let db = test_db().await;
+        let (chain_id, token) = token_address("XAG");
+        clear_token(&db, chain_id, &token).await;
+        let hour = 3600;
+        let hourly: Vec<_> = [
+            (0, "10", "12", "9", "11"),
+            (1, "11", "15", "11", "14"),
+            (4, "14", "14", "8", "9"),
+            (5, "9", "10", "9", "10"),
+        ]
+        .iter()
+        .map(|(hours, open, high, low, close)| CandleBar {
+            timestamp: hours * hour,
+            open: open.parse().unwrap(),
+            high: high.parse().unwrap(),
+            low: low.parse().unwrap(),
+            close: close.parse().unwrap(),
+        })
+        .collect();
+        insert_bars(&db, chain_id, &token, "1h", &hourly).await;
+        let client = client(db).await;
+
+        let (status, body) = get(&client, "/prices/candles?period=4h&token_symbol=XAG").await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["period"], "4h");
+        let bar = |timestamp: i64, open: &str, high: &str, low: &str, close: &str| {
+            (timestamp, open.into(), high.into(), low.into(), close.into())
+        };
+        assert_eq!(
+            rows(&body),
+            vec![bar(4 * hour, "14", "14", "8", "10"), bar(0, "10", "15", "9", "14")]
+        );
     }
 }
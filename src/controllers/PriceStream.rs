@@ -0,0 +1,69 @@
+use crate::configs::envconfig::resolve_chain_id;
+use crate::utils::eventsutils::PriceEvents;
+use crate::utils::helpersutils::{SUPPORTED_PERIODS, SUPPORTED_TOKENS};
+use crate::utils::apierrorutils::ApiError;
+use rocket::response::stream::{Event, EventStream};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::{get, Shutdown, State};
+
+// Server-sent events with every new aggregated price ("price") and candle
+// update ("candle"). Repeat `token` and `period` to subscribe to several;
+// leaving one out subscribes to all of them.
+#[allow(non_snake_case)]
+#[get("/stream?<token>&<period>&<chainId>")]
+pub async fn get_price_stream(
+    token: Vec<String>,
+    period: Vec<String>,
+    chainId: Option<i64>,
+    events: &State<PriceEvents>,
+    mut end: Shutdown,
+) -> Result<EventStream![], ApiError> {
+    let chain_id = match resolve_chain_id(chainId) {
+        Some(id) => id,
+        None => return Err(ApiError::UnsupportedChain),
+    };
+
+    let supported_tokens = SUPPORTED_TOKENS.get(&chain_id).cloned().unwrap_or_default();
+    if let Some(unsupported) = token.iter().find(|token| !supported_tokens.contains(&token.as_str())) {
+        return Err(ApiError::UnsupportedToken(format!(
+            "Unsupported token {}. Supported: {:?}", unsupported, supported_tokens
+        )));
+    }
+    if let Some(unsupported) = period.iter().find(|period| !SUPPORTED_PERIODS.contains(&period.as_str())) {
+        return Err(ApiError::UnsupportedPeriod(format!(
+            "Unsupported period {}. Supported: {:?}", unsupported, *SUPPORTED_PERIODS
+        )));
+    }
+
+    let mut receiver = events.0.subscribe();
+
+    Ok(EventStream! {
+        loop {
+            let event = select! {
+                message = receiver.recv() => match message {
+                    Ok(event) => event,
+                    // Dropped events are not replayed; the next ones follow
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                _ = &mut end => break,
+            };
+
+            if event.chain_id() != chain_id {
+                continue;
+            }
+            if !token.is_empty() && !token.iter().any(|token| token == event.token_symbol()) {
+                continue;
+            }
+            if let Some(event_period) = event.period()
+                && !period.is_empty()
+                && !period.iter().any(|period| period == event_period)
+            {
+                continue;
+            }
+
+            yield Event::json(&event).event(event.name());
+        }
+    })
+}
@@ -1,12 +1,25 @@
+use crate::configs::envconfig::resolve_chain_id;
+use crate::utils::apierrorutils::ApiError;
 use crate::utils::interfaceutils::AssetPricingInfo2;
+use crate::utils::latestpricesutils::LatestPriceCache;
 use crate::utils::pricesutils::get_token_prices_filtered;
 use rocket::{get, serde::json::Json};
 use crate::DbConnection;
 use rocket::State;
 
 
-#[get("/tickers")] 
-pub async fn getPriceTickers(db: &State<DbConnection>)-> Json<Vec<AssetPricingInfo2>>{
-    let tickersData = get_token_prices_filtered(&db.0).await;
-    Json(tickersData)
+#[allow(non_snake_case)]
+#[get("/tickers?<chainId>")] 
+pub async fn getPriceTickers(
+    chainId: Option<i64>,
+    db: &State<DbConnection>,
+    cache: &State<LatestPriceCache>
+) -> Result<Json<Vec<AssetPricingInfo2>>, ApiError> {
+    let chain_id = match resolve_chain_id(chainId) {
+        Some(id) => id,
+        None => return Err(ApiError::UnsupportedChain),
+    };
+
+    let tickersData = get_token_prices_filtered(&db.0, cache, chain_id).await?;
+    Ok(Json(tickersData))
 }
@@ -0,0 +1,242 @@
+use crate::configs::envconfig::resolve_chain_id;
+use crate::configs::registryconfig::{AssetRecord, REGISTRY};
+use crate::utils::apierrorutils::ApiError;
+use crate::utils::calendarutils::TRADING_CALENDAR;
+use crate::utils::candlesutils::{get_candles, CandleOrder, CandleRange};
+use crate::utils::latestpricesutils::unix_now;
+use crate::utils::pricesutils::calculate_price_decimals;
+use crate::DbConnection;
+use rocket::{get, serde::json::Json, State};
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::JsonValue;
+use serde_json::json;
+
+// Bars returned by one /history request when no countback is given.
+const MAX_HISTORY_BARS: u64 = 5000;
+const MAX_SEARCH_RESULTS: usize = 50;
+
+// TradingView resolution -> SUPPORTED_PERIODS entry.
+const UDF_RESOLUTIONS: [(&str, &str); 6] = [
+    ("1", "1m"),
+    ("5", "5m"),
+    ("15", "15m"),
+    ("60", "1h"),
+    ("240", "4h"),
+    ("1D", "1d"),
+];
+
+fn resolution_to_period(resolution: &str) -> Option<&'static str> {
+    // "D" is shorthand for "1D"
+    let resolution = if resolution == "D" { "1D" } else { resolution };
+    UDF_RESOLUTIONS
+        .iter()
+        .find(|(udf, _)| *udf == resolution)
+        .map(|(_, period)| *period)
+}
+
+fn supported_resolutions() -> Vec<&'static str> {
+    UDF_RESOLUTIONS.iter().map(|(udf, _)| *udf).collect()
+}
+
+// Protocol-level errors the charting library understands; request and server
+// failures go through ApiError instead.
+fn udf_error(errmsg: &str) -> Result<Json<JsonValue>, ApiError> {
+    Ok(Json(json!({ "s": "error", "errmsg": errmsg })))
+}
+
+fn find_listed_asset(chain_id: i64, symbol: &str) -> Option<&'static AssetRecord> {
+    REGISTRY
+        .listed_assets(chain_id)
+        .find(|asset| asset.symbol.eq_ignore_ascii_case(symbol))
+}
+
+#[get("/config")]
+pub async fn udf_config() -> Json<JsonValue> {
+    Json(json!({
+        "supported_resolutions": supported_resolutions(),
+        "supports_search": true,
+        "supports_group_request": false,
+        "supports_marks": false,
+        "supports_timescale_marks": false,
+        "supports_time": true,
+        "exchanges": [],
+        "symbols_types": [
+            { "name": "All types", "value": "" },
+            { "name": "Crypto", "value": "crypto" },
+            { "name": "Forex", "value": "forex" },
+            { "name": "Real estate", "value": "real_estate" },
+            { "name": "Commodity", "value": "commodity" },
+            { "name": "Bond", "value": "bond" },
+        ],
+    }))
+}
+
+#[allow(non_snake_case)]
+#[get("/symbols?<symbol>&<chainId>")]
+pub async fn udf_symbols(
+    symbol: String,
+    chainId: Option<i64>,
+    db: &State<DbConnection>,
+) -> Result<Json<JsonValue>, ApiError> {
+    let chain_id = match resolve_chain_id(chainId) {
+        Some(id) => id,
+        None => return Err(ApiError::UnsupportedChain),
+    };
+    let asset = match find_listed_asset(chain_id, &symbol) {
+        Some(asset) => asset,
+        None => return udf_error("unknown_symbol"),
+    };
+
+    // Price scale follows the latest close in the class's latest period, like
+    // the ticker's price_decimals
+    let mut price_decimals = 0;
+    if let Some(token) = &asset.address {
+        let period = &REGISTRY.class_config(asset.asset_class).latest_period;
+        let newest = CandleOrder::NewestFirst;
+        let latest = get_candles(&db.0, chain_id, token, period, &CandleRange::default(), newest, 1)
+            .await?;
+        if let Some(candle) = latest.first() {
+            price_decimals = calculate_price_decimals(candle.close).await.unwrap_or(0);
+        }
+    }
+
+    let schedule = TRADING_CALENDAR.for_asset(asset);
+
+    Ok(Json(json!({
+        "name": asset.symbol,
+        "ticker": asset.symbol,
+        "description": asset.symbol,
+        "type": asset.asset_class.as_str(),
+        "session": schedule.udf_session(),
+        "session_holidays": schedule.udf_holidays(),
+        "timezone": "Etc/UTC",
+        "exchange": "",
+        "listed_exchange": "",
+        "minmov": 1,
+        "pricescale": 10u64.pow(price_decimals.min(18) as u32),
+        "has_intraday": true,
+        "has_daily": true,
+        "intraday_multipliers": ["1", "5", "15", "60", "240"],
+        "supported_resolutions": supported_resolutions(),
+        "volume_precision": 0,
+        "data_status": "streaming",
+    })))
+}
+
+#[allow(non_snake_case)]
+#[get("/search?<query>&<type>&<exchange>&<limit>&<chainId>")]
+pub async fn udf_search(
+    query: String,
+    r#type: Option<String>,
+    exchange: Option<String>,
+    limit: Option<usize>,
+    chainId: Option<i64>,
+) -> Result<Json<JsonValue>, ApiError> {
+    let chain_id = match resolve_chain_id(chainId) {
+        Some(id) => id,
+        None => return Err(ApiError::UnsupportedChain),
+    };
+    // All assets are served without an exchange
+    if exchange.is_some_and(|exchange| !exchange.is_empty()) {
+        return Ok(Json(json!([])));
+    }
+
+    let query = query.to_uppercase();
+    let asset_type = r#type.filter(|asset_type| !asset_type.is_empty());
+    let limit = limit.unwrap_or(MAX_SEARCH_RESULTS).min(MAX_SEARCH_RESULTS);
+
+    let results = REGISTRY
+        .listed_assets(chain_id)
+        .filter(|asset| asset.symbol.to_uppercase().contains(&query))
+        .filter(|asset| match &asset_type {
+            Some(asset_type) => asset.asset_class.as_str() == asset_type,
+            None => true,
+        })
+        .take(limit)
+        .map(|asset| {
+            json!({
+                "symbol": asset.symbol,
+                "full_name": asset.symbol,
+                "description": asset.symbol,
+                "exchange": "",
+                "ticker": asset.symbol,
+                "type": asset.asset_class.as_str(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(json!(results)))
+}
+
+// `from` is inclusive and `to` exclusive, as sent by the charting library.
+// With `countback`, the latest `countback` bars before `to` are returned
+// regardless of `from`; otherwise up to MAX_HISTORY_BARS bars from `from` on.
+#[allow(non_snake_case)]
+#[get("/history?<symbol>&<resolution>&<from>&<to>&<countback>&<chainId>")]
+pub async fn udf_history(
+    symbol: String,
+    resolution: String,
+    from: i64,
+    to: i64,
+    countback: Option<u64>,
+    chainId: Option<i64>,
+    db: &State<DbConnection>,
+) -> Result<Json<JsonValue>, ApiError> {
+    let chain_id = match resolve_chain_id(chainId) {
+        Some(id) => id,
+        None => return Err(ApiError::UnsupportedChain),
+    };
+    let token = match find_listed_asset(chain_id, &symbol).and_then(|asset| asset.address.as_ref()) {
+        Some(token) => token,
+        None => return udf_error("unknown_symbol"),
+    };
+    let period = match resolution_to_period(&resolution) {
+        Some(period) => period,
+        None => return udf_error("Unsupported resolution"),
+    };
+
+    let (range, order, limit) = match countback {
+        Some(countback) => (
+            CandleRange { from: None, to: None, before: Some(to), after: None },
+            CandleOrder::NewestFirst,
+            countback.min(MAX_HISTORY_BARS),
+        ),
+        None => (
+            CandleRange { from: Some(from), to: None, before: Some(to), after: None },
+            CandleOrder::OldestFirst,
+            MAX_HISTORY_BARS,
+        ),
+    };
+
+    let mut bars = get_candles(&db.0, chain_id, token, period, &range, order, limit).await?;
+
+    if bars.is_empty() {
+        // Tell the library where older data resumes, if there is any
+        let older = CandleRange { from: None, to: None, before: Some(from), after: None };
+        let older_bars =
+            get_candles(&db.0, chain_id, token, period, &older, CandleOrder::NewestFirst, 1).await?;
+        return Ok(match older_bars.first() {
+            Some(bar) => Json(json!({ "s": "no_data", "nextTime": bar.timestamp })),
+            None => Json(json!({ "s": "no_data" })),
+        });
+    }
+
+    // The library plots floats; ascending order is required
+    if order == CandleOrder::NewestFirst {
+        bars.reverse();
+    }
+    let to_f64 = |value: rust_decimal::Decimal| value.to_f64().unwrap_or_default();
+    Ok(Json(json!({
+        "s": "ok",
+        "t": bars.iter().map(|bar| bar.timestamp).collect::<Vec<_>>(),
+        "o": bars.iter().map(|bar| to_f64(bar.open)).collect::<Vec<_>>(),
+        "h": bars.iter().map(|bar| to_f64(bar.high)).collect::<Vec<_>>(),
+        "l": bars.iter().map(|bar| to_f64(bar.low)).collect::<Vec<_>>(),
+        "c": bars.iter().map(|bar| to_f64(bar.close)).collect::<Vec<_>>(),
+    })))
+}
+
+#[get("/time")]
+pub async fn udf_time() -> String {
+    unix_now().to_string()
+}
@@ -7,4 +7,8 @@ pub mod PriceTickers;
 #[allow(non_snake_case)]
 pub mod ReportUI;
 #[allow(non_snake_case)]
-pub mod IncentivesStip;
\ No newline at end of file
+pub mod IncentivesStip;
+#[allow(non_snake_case)]
+pub mod TradingViewUdf;
+#[allow(non_snake_case)]
+pub mod PriceStream;
\ No newline at end of file
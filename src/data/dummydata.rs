@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use once_cell::sync::Lazy;
 
 
-pub static dummyData: Lazy<HashMap<String, HashMap<String, bool>>> = Lazy::new(|| {
+pub static DUMMY_DATA: Lazy<HashMap<String, HashMap<String, bool>>> = Lazy::new(|| {
     let mut map = HashMap::new();
     
     let mut lp_map = HashMap::new();
@@ -0,0 +1,472 @@
+use crate::configs::envconfig::{CandleOpenMode, ENV};
+use crate::entities::{prelude::*, *};
+use crate::utils::aggregationutils::AggregatedPrice;
+use crate::utils::candlesutils::CandleBar;
+use crate::utils::helpersutils::PERIOD_MAP;
+use rust_decimal::Decimal;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{DatabaseConnection, DbBackend, DbErr, EntityTrait, Set, Statement};
+use std::collections::HashMap;
+
+// Rows per INSERT, kept well below Postgres' bind parameter limit.
+const CANDLE_FLUSH_BATCH_SIZE: usize = 1000;
+
+#[derive(Clone, Debug)]
+struct BuiltCandle {
+    token: String,
+    period: String,
+    timestamp: i64,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+    aggregation_strategy: Option<String>,
+    price_sources: Option<String>,
+    spread: Option<Decimal>,
+    // Changed since the last flush.
+    dirty: bool,
+}
+
+impl BuiltCandle {
+    fn from_model(candle: price_candle::Model) -> Self {
+        BuiltCandle {
+            token: candle.token,
+            period: candle.period,
+            timestamp: candle.timestamp,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            aggregation_strategy: candle.aggregation_strategy,
+            price_sources: candle.price_sources,
+            spread: candle.spread,
+            dirty: false,
+        }
+    }
+
+    fn to_bar(&self) -> CandleBar {
+        CandleBar {
+            timestamp: self.timestamp,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+        }
+    }
+
+    fn to_active_model(&self, chain_id: i64) -> price_candle::ActiveModel {
+        price_candle::ActiveModel {
+            token: Set(self.token.clone()),
+            open: Set(self.open),
+            high: Set(self.high),
+            low: Set(self.low),
+            close: Set(self.close),
+            timestamp: Set(self.timestamp),
+            period: Set(self.period.clone()),
+            chain_id: Set(chain_id),
+            aggregation_strategy: Set(self.aggregation_strategy.clone()),
+            price_sources: Set(self.price_sources.clone()),
+            spread: Set(self.spread),
+            ..Default::default()
+        }
+    }
+}
+
+// Keeps the open candle of every (token, period) of one chain in memory and
+// writes the changed ones to price_candle in batched upserts.
+pub struct CandleBuilder {
+    chain_id: i64,
+    open_mode: CandleOpenMode,
+    open_candles: HashMap<(String, String), BuiltCandle>,
+    // Candles replaced by a newer bucket that still need their last flush.
+    closed_candles: Vec<BuiltCandle>,
+}
+
+impl CandleBuilder {
+    // Rebuilds the open candles from the newest stored candle of every
+    // (token, period) of the chain.
+    pub async fn load(db: &DatabaseConnection, chain_id: i64) -> Result<Self, DbErr> {
+        let latest_candles = PriceCandle::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"SELECT DISTINCT ON (token, period) * FROM price_candle
+                   WHERE chain_id = $1
+                   ORDER BY token, period, timestamp DESC"#,
+                [chain_id.into()],
+            ))
+            .all(db)
+            .await?;
+
+        let open_candles = latest_candles
+            .into_iter()
+            .map(|candle| {
+                let candle = BuiltCandle::from_model(candle);
+                ((candle.token.clone(), candle.period.clone()), candle)
+            })
+            .collect();
+
+        Ok(CandleBuilder {
+            chain_id,
+            open_mode: ENV.CANDLE_OPEN_MODE,
+            open_candles,
+            closed_candles: Vec::new(),
+        })
+    }
+
+    // Applies one price of `token` at `timestamp_val` to the candle of every
+    // period and returns the candles it changed.
+    pub fn record(
+        &mut self,
+        timestamp_val: u64,
+        token: &str,
+        aggregated: &AggregatedPrice,
+    ) -> Vec<(&'static str, CandleBar)> {
+        PERIOD_MAP
+            .iter()
+            .filter_map(|(period, interval)| {
+                self.record_period(timestamp_val, token, aggregated, period, *interval)
+                    .map(|candle| (*period, candle))
+            })
+            .collect()
+    }
+
+    fn record_period(
+        &mut self,
+        timestamp_val: u64,
+        token: &str,
+        aggregated: &AggregatedPrice,
+        period: &str,
+        interval: u64,
+    ) -> Option<CandleBar> {
+        let price = aggregated.decimal_price()?;
+        let current_timestamp = ((timestamp_val / interval) * interval) as i64;
+        let key = (token.to_string(), period.to_string());
+        let aggregation_strategy = Some(aggregated.strategy.as_str().to_string());
+        let price_sources = Some(aggregated.sources_label());
+        let spread = aggregated.decimal_spread();
+
+        if let Some(candle) = self.open_candles.get_mut(&key) {
+            if candle.timestamp == current_timestamp {
+                candle.close = price;
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.aggregation_strategy = aggregation_strategy;
+                candle.price_sources = price_sources;
+                candle.spread = spread;
+                candle.dirty = true;
+                return Some(candle.to_bar());
+            }
+            if candle.timestamp > current_timestamp {
+                // Late update for a bucket that has already rolled over.
+                return None;
+            }
+        }
+
+        let mut open_val = price;
+
+        if let Some(mut last_candle) = self.open_candles.remove(&key) {
+            if last_candle.timestamp == current_timestamp - interval as i64 {
+                match self.open_mode {
+                    CandleOpenMode::PreviousClose => open_val = last_candle.close,
+                    CandleOpenMode::FirstPrice => {}
+                    CandleOpenMode::Smoothed => {
+                        open_val = (price + last_candle.close) / Decimal::TWO;
+
+                        // Update the last candle's close value, widening its
+                        // range to keep the close inside it
+                        last_candle.close = open_val;
+                        last_candle.high = last_candle.high.max(open_val);
+                        last_candle.low = last_candle.low.min(open_val);
+                        last_candle.dirty = true;
+                    }
+                }
+            }
+            if last_candle.dirty {
+                self.closed_candles.push(last_candle);
+            }
+        }
+
+        let candle = BuiltCandle {
+            token: token.to_string(),
+            period: period.to_string(),
+            timestamp: current_timestamp,
+            open: open_val,
+            high: open_val.max(price),
+            low: open_val.min(price),
+            close: price,
+            aggregation_strategy,
+            price_sources,
+            spread,
+            dirty: true,
+        };
+        let bar = candle.to_bar();
+        self.open_candles.insert(key, candle);
+        Some(bar)
+    }
+
+    // Upserts every changed candle. On error nothing is marked clean, so the
+    // next flush retries the same candles.
+    pub async fn flush(&mut self, db: &DatabaseConnection) -> Result<usize, DbErr> {
+        let dirty_candles: Vec<price_candle::ActiveModel> = self
+            .closed_candles
+            .iter()
+            .chain(self.open_candles.values().filter(|candle| candle.dirty))
+            .map(|candle| candle.to_active_model(self.chain_id))
+            .collect();
+
+        if dirty_candles.is_empty() {
+            return Ok(0);
+        }
+
+        for batch in dirty_candles.chunks(CANDLE_FLUSH_BATCH_SIZE) {
+            PriceCandle::insert_many(batch.to_vec())
+                .on_conflict(
+                    OnConflict::columns([
+                        price_candle::Column::ChainId,
+                        price_candle::Column::Token,
+                        price_candle::Column::Period,
+                        price_candle::Column::Timestamp,
+                    ])
+                    // The stored open is kept: the candle may have been
+                    // opened by another submitter or before a restart.
+                    .update_columns([
+                        price_candle::Column::Close,
+                        price_candle::Column::AggregationStrategy,
+                        price_candle::Column::PriceSources,
+                        price_candle::Column::Spread,
+                    ])
+                    // Keep extremes written by another submitter for the same
+                    // candle.
+                    .value(
+                        price_candle::Column::High,
+                        Expr::cust("GREATEST(price_candle.high, EXCLUDED.high)"),
+                    )
+                    .value(
+                        price_candle::Column::Low,
+                        Expr::cust("LEAST(price_candle.low, EXCLUDED.low)"),
+                    )
+                    .to_owned(),
+                )
+                .exec(db)
+                .await?;
+        }
+
+        self.closed_candles.clear();
+        for candle in self.open_candles.values_mut() {
+            candle.dirty = false;
+        }
+
+        Ok(dirty_candles.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::aggregationutils::AggregationStrategy;
+    use crate::utils::testdbutils::{clear_chain, test_db};
+    use sea_orm::{ColumnTrait, QueryFilter, QueryOrder};
+
+    const MINUTE: u64 = 60;
+
+    fn new_builder(chain_id: i64, open_mode: CandleOpenMode) -> CandleBuilder {
+        CandleBuilder {
+            chain_id,
+            open_mode,
+            open_candles: HashMap::new(),
+            closed_candles: Vec::new(),
+        }
+    }
+
+    fn price(price: f64) -> AggregatedPrice {
+        AggregatedPrice {
+            price,
+            strategy: AggregationStrategy::Median,
+            sources: vec!["binance"],
+            spread: 0.0,
+        }
+    }
+
+    fn record(builder: &mut CandleBuilder, timestamp: u64, value: f64) -> Option<CandleBar> {
+        builder.record_period(timestamp, "BTC", &price(value), "1m", MINUTE)
+    }
+
+    fn decimal(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn open_candle(builder: &CandleBuilder) -> &BuiltCandle {
+        &builder.open_candles[&("BTC".to_string(), "1m".to_string())]
+    }
+
+    #[test]
+    fn updates_the_open_candle() {
+        let mut builder = new_builder(1, CandleOpenMode::PreviousClose);
+        record(&mut builder, 600, 100.0);
+        record(&mut builder, 610, 105.0);
+        record(&mut builder, 659, 98.0);
+
+        let candle = open_candle(&builder);
+        assert_eq!(candle.timestamp, 600);
+        assert_eq!(
+            (candle.open, candle.high, candle.low, candle.close),
+            (decimal("100"), decimal("105"), decimal("98"), decimal("98"))
+        );
+        assert!(candle.dirty);
+        assert!(builder.closed_candles.is_empty());
+    }
+
+    #[test]
+    fn rolls_over_into_the_next_bucket() {
+        let mut builder = new_builder(1, CandleOpenMode::PreviousClose);
+        record(&mut builder, 600, 100.0);
+        record(&mut builder, 660, 120.0);
+
+        // The new candle opens at the previous close, which is kept for the
+        // next flush unchanged.
+        let candle = open_candle(&builder);
+        assert_eq!(candle.timestamp, 660);
+        assert_eq!(
+            (candle.open, candle.low, candle.close),
+            (decimal("100"), decimal("100"), decimal("120"))
+        );
+        assert_eq!(builder.closed_candles.len(), 1);
+        assert_eq!(builder.closed_candles[0].close, decimal("100"));
+
+        // After a gap the first price opens the candle.
+        record(&mut builder, 900, 130.0);
+        assert_eq!(open_candle(&builder).open, decimal("130"));
+        assert_eq!(builder.closed_candles.len(), 2);
+    }
+
+    #[test]
+    fn first_price_opens_at_the_first_price() {
+        let mut builder = new_builder(1, CandleOpenMode::FirstPrice);
+        record(&mut builder, 600, 100.0);
+        record(&mut builder, 660, 110.0);
+        assert_eq!(open_candle(&builder).open, decimal("110"));
+    }
+
+    #[test]
+    fn smoothed_keeps_the_rewritten_close_inside_the_range() {
+        let mut builder = new_builder(1, CandleOpenMode::Smoothed);
+        record(&mut builder, 600, 100.0);
+        record(&mut builder, 660, 120.0);
+        assert_eq!(open_candle(&builder).open, decimal("110"));
+
+        let closed = &builder.closed_candles[0];
+        assert_eq!(closed.close, decimal("110"));
+        assert_eq!(closed.high, decimal("110"));
+        assert_eq!(closed.low, decimal("100"));
+
+        let mut builder = new_builder(1, CandleOpenMode::Smoothed);
+        record(&mut builder, 600, 100.0);
+        record(&mut builder, 660, 80.0);
+        let closed = &builder.closed_candles[0];
+        assert_eq!(closed.close, decimal("90"));
+        assert_eq!(closed.high, decimal("100"));
+        assert_eq!(closed.low, decimal("90"));
+    }
+
+    #[test]
+    fn drops_late_updates() {
+        let mut builder = new_builder(1, CandleOpenMode::PreviousClose);
+        record(&mut builder, 660, 100.0);
+        assert!(record(&mut builder, 650, 1.0).is_none());
+
+        let candle = open_candle(&builder);
+        assert_eq!(candle.timestamp, 660);
+        assert_eq!((candle.low, candle.close), (decimal("100"), decimal("100")));
+        assert!(builder.closed_candles.is_empty());
+    }
+
+    async fn stored_candles(db: &DatabaseConnection, chain_id: i64) -> Vec<price_candle::Model> {
+        PriceCandle::find()
+            .filter(price_candle::Column::ChainId.eq(chain_id))
+            .filter(price_candle::Column::Period.eq("1m"))
+            .order_by_asc(price_candle::Column::Timestamp)
+            .all(db)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn flush_upserts_changed_candles() {
+        let chain_id = 908_001;
+        let db = test_db().await;
+        clear_chain(&db, chain_id).await;
+
+        let mut builder = new_builder(chain_id, CandleOpenMode::PreviousClose);
+        record(&mut builder, 600, 100.0);
+        assert_eq!(builder.flush(&db).await.unwrap(), 1);
+        // Nothing changed since.
+        assert_eq!(builder.flush(&db).await.unwrap(), 0);
+
+        record(&mut builder, 610, 104.0);
+        record(&mut builder, 660, 102.0);
+        assert_eq!(builder.flush(&db).await.unwrap(), 2);
+
+        let stored = stored_candles(&db, chain_id).await;
+        let prices: Vec<(i64, Decimal, Decimal, Decimal)> = stored
+            .iter()
+            .map(|candle| (candle.timestamp, candle.open, candle.high, candle.close))
+            .collect();
+        assert_eq!(
+            prices,
+            vec![
+                (600, decimal("100"), decimal("104"), decimal("104")),
+                (660, decimal("104"), decimal("104"), decimal("102")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn flush_keeps_the_stored_open() {
+        let chain_id = 908_003;
+        let db = test_db().await;
+        clear_chain(&db, chain_id).await;
+
+        let mut builder = new_builder(chain_id, CandleOpenMode::PreviousClose);
+        record(&mut builder, 600, 100.0);
+        builder.flush(&db).await.unwrap();
+
+        // A builder that never saw the stored candle only moves its close and
+        // widens its range.
+        let mut builder = new_builder(chain_id, CandleOpenMode::PreviousClose);
+        record(&mut builder, 630, 120.0);
+        builder.flush(&db).await.unwrap();
+
+        let stored = stored_candles(&db, chain_id).await;
+        assert_eq!(
+            (stored[0].open, stored[0].high, stored[0].low, stored[0].close),
+            (decimal("100"), decimal("120"), decimal("100"), decimal("120"))
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn load_rebuilds_the_open_candles() {
+        let chain_id = 908_002;
+        let db = test_db().await;
+        clear_chain(&db, chain_id).await;
+
+        let mut builder = new_builder(chain_id, CandleOpenMode::PreviousClose);
+        record(&mut builder, 600, 100.0);
+        record(&mut builder, 660, 110.0);
+        builder.flush(&db).await.unwrap();
+
+        // The newest stored candle is open again after a restart.
+        let mut builder = CandleBuilder::load(&db, chain_id).await.unwrap();
+        let candle = open_candle(&builder);
+        assert_eq!((candle.timestamp, candle.close), (660, decimal("110")));
+        assert!(!candle.dirty);
+
+        record(&mut builder, 700, 90.0);
+        builder.flush(&db).await.unwrap();
+        let stored = stored_candles(&db, chain_id).await;
+        assert_eq!(stored.len(), 2);
+        assert_eq!((stored[1].low, stored[1].close), (decimal("90"), decimal("90")));
+    }
+}
@@ -1,14 +1,61 @@
-
-
+use crate::assets::bonds::feed::bondfeed::run_bond_feed;
+use crate::assets::commodity::feed::commodityfeed::run_commodity_feed;
+use crate::assets::realestate::feed::realestatefeed::run_realestate_feed;
+use crate::configs::envconfig::SERVED_CHAIN_IDS;
 use crate::jobs::pricesubmitter::submit_prices;
+use crate::utils::eventsutils::PriceEvents;
+use crate::utils::latestpricesutils::LatestPriceCache;
+use futures::future::join_all;
 use sea_orm::*;
 use dotenv::dotenv;
 use std::env;
 
 
-pub async fn executejobs(){
+// What the submitters share with the API server.
+#[derive(Clone)]
+pub struct LiveFeeds {
+    // Every price and candle update, for the streaming API.
+    pub events: PriceEvents,
+    // Newest price per token, for the tickers.
+    pub latest_prices: LatestPriceCache,
+}
+
+pub async fn executejobs(feeds: LiveFeeds){
     dotenv().ok();
     let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let db = Database::connect(&db_url).await.unwrap();
-    let _ = submit_prices(&db).await;
+
+    // Bond yields and commodities come from investing charts and real estate
+    // from Parcl, rather than from the submitters.
+    let bond_feed = {
+        let db = db.clone();
+        let latest_prices = feeds.latest_prices.clone();
+        tokio::spawn(async move { run_bond_feed(&db, latest_prices).await })
+    };
+    let commodity_feed = {
+        let db = db.clone();
+        let latest_prices = feeds.latest_prices.clone();
+        tokio::spawn(async move { run_commodity_feed(&db, latest_prices).await })
+    };
+    let realestate_feed = {
+        let db = db.clone();
+        let latest_prices = feeds.latest_prices.clone();
+        tokio::spawn(async move { run_realestate_feed(&db, latest_prices).await })
+    };
+
+    // One submitter loop per served network, each with its own candle stream.
+    let submitters = SERVED_CHAIN_IDS.iter().map(|chain_id| {
+        let db = db.clone();
+        let feeds = feeds.clone();
+        let chain_id = *chain_id;
+        tokio::spawn(async move {
+            if let Err(e) = submit_prices(&db, chain_id, feeds).await {
+                eprintln!("Price submitter for chain {} stopped: {}", chain_id, e);
+            }
+        })
+    });
+    join_all(submitters).await;
+    bond_feed.abort();
+    commodity_feed.abort();
+    realestate_feed.abort();
 }
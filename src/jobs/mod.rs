@@ -1,3 +1,3 @@
-
 pub mod pricesubmitter;
+pub mod candlebuilder;
 pub mod index;
\ No newline at end of file
@@ -1,136 +1,179 @@
-#[path = "../../entity/src/mod.rs"]
-mod entities;
-
-use crate::configs::envconfig::{CHAINID_MAP, ENV};
 use crate::utils::helpersutils::{
-    PERIOD_MAP, TOKENS_MAPPINGS
+    CANDLE_FLUSH_INTERVAL, PRICE_FETCH_INTERVAL, TOKENS_MAPPINGS
 };
-use crate::utils::pricesutils::get_token_prices;
-use entities::{prelude::*, *};
-use sea_orm::entity::prelude::*;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set};
+use crate::jobs::candlebuilder::CandleBuilder;
+use crate::configs::envconfig::{PythFeedMode, ENV};
+use crate::configs::registryconfig::{PriceFeedKind, REGISTRY};
+use crate::utils::aggregationutils::{AggregatedPrice, PriceSample};
+use crate::utils::eventsutils::PriceEvent;
+use crate::jobs::index::LiveFeeds;
+use crate::utils::calendarutils::TRADING_CALENDAR;
+use crate::utils::latestpricesutils::LatestPrice;
+use crate::utils::exchangesutils::get_exchange_prices;
+use crate::utils::pricesutils::{get_pyth_prices, get_token_prices};
+use crate::utils::pythstreamutils::stream_pyth_prices;
+use crate::utils::pythvalidationutils::{is_stale, record_pyth_rejection, PythRejectReason};
+use sea_orm::{DatabaseConnection, DbErr};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::time::{interval, MissedTickBehavior};
+
+const PYTH_UPDATE_CHANNEL_SIZE: usize = 64;
+
+
+// Records the aggregated prices of the chain's listed tokens in the candle
+// builder and the latest-price cache, and publishes the prices and updated
+// candles; nothing is written until the next flush. Tokens whose market is
+// closed are skipped, so no flat candles are built over closed sessions.
+pub fn submit_new_prices_to_builder(
+    builder: &mut CandleBuilder,
+    feeds: &LiveFeeds,
+    chain_id: i64,
+    prices: HashMap<String, AggregatedPrice>,
+) {
+    let tokens = TOKENS_MAPPINGS
+        .get(&chain_id)
+        .expect("Cannot get tokens from token mappings");
 
+    let timestamp_val = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
 
-pub async fn store_price_candle(
-    db: &DatabaseConnection,
-    timestamp_val: u64,
-    token_val: String,
-    price: f64,
-    period_val: String,
-    interval: u64,
-) -> Result<(), DbErr> {
-    let current_timestamp = (timestamp_val / interval) * interval;
-    let icurrent_timestamp = current_timestamp as i64;
-
-    let network_chain_id = CHAINID_MAP
-        .get(&ENV.NETWORK)
-        .expect("Expected CHAINID_MAP key");
-
-    // Check if the candle exists
-    let existing_candle = PriceCandle::find()
-        .filter(price_candle::Column::ChainId.eq(*network_chain_id))
-        .filter(price_candle::Column::Token.eq(&token_val))
-        .filter(price_candle::Column::Timestamp.eq(icurrent_timestamp))
-        .filter(price_candle::Column::Period.eq(&period_val))
-        .one(db)
-        .await?;
-
-    match existing_candle {
-        Some(candle) => {
-            let mut candle: price_candle::ActiveModel = candle.into();
-            candle.close = Set(price as f32);
-            candle.high = Set(f32::max(candle.high.unwrap(), price as f32));
-            candle.low = Set(f32::min(candle.low.unwrap(), price as f32));
-
-            candle.update(db).await?;
+    for token_val in tokens.iter() {
+        if !TRADING_CALENDAR.is_open(chain_id, &token_val.key, timestamp_val as i64) {
+            continue;
         }
-        None => {
-            let last_timestamp = icurrent_timestamp - interval as i64;
-            let mut open_val = price;
-
-            // Fetch the last candle
-            let last_candle = PriceCandle::find()
-                .filter(price_candle::Column::ChainId.eq(*network_chain_id))
-                .filter(price_candle::Column::Token.eq(&token_val))
-                .filter(price_candle::Column::Timestamp.eq(last_timestamp))
-                .filter(price_candle::Column::Period.eq(&period_val))
-                .one(db)
-                .await?;
-
-            if let Some(last_candle) = last_candle {
-                open_val = (price + last_candle.close as f64) / 2.0;
-
-                // Update the last candle's close value
-                let mut last_candle: price_candle::ActiveModel = last_candle.into();
-                last_candle.close = Set(open_val as f32);
-                last_candle.update(db).await?;
+        if let Some(price) = prices.get(&token_val.key) {
+            let candles = builder.record(timestamp_val, &token_val.address, price);
+
+            if let Some(decimal_price) = price.decimal_price() {
+                feeds.latest_prices.update(
+                    chain_id,
+                    &token_val.key,
+                    LatestPrice {
+                        price: decimal_price,
+                        spread: price.decimal_spread(),
+                        updated_at: timestamp_val,
+                    },
+                );
+            }
+            if let Some(event) =
+                PriceEvent::price(chain_id, &token_val.key, &token_val.address, price, timestamp_val)
+            {
+                feeds.events.publish(event);
+            }
+            for (period, candle) in candles {
+                feeds.events.publish(PriceEvent::Candle {
+                    chain_id,
+                    token_symbol: token_val.key.clone(),
+                    token_address: token_val.address.clone(),
+                    period,
+                    candle,
+                });
             }
-
-            let high_val = f64::max(open_val, price);
-            let low_val = f64::min(open_val, price);
-
-            // Create a new candle
-            let new_candle = price_candle::ActiveModel {
-                token: Set(token_val),
-                open: Set(open_val as f32),
-                high: Set(high_val as f32),
-                low: Set(low_val as f32),
-                close: Set(price as f32),
-                timestamp: Set(icurrent_timestamp),
-                period: Set(period_val),
-                chain_id: Set(*network_chain_id),
-                ..Default::default()
-            };
-
-            new_candle.insert(db).await?;
         }
     }
-
-    Ok(())
 }
 
-pub async fn submit_new_prices_to_db(db: &DatabaseConnection, prices: HashMap<String, f64>) -> Result<(), DbErr> {
-    let tokens = TOKENS_MAPPINGS
-        .get(&ENV.NETWORK)
-        .expect("Cannot get tokens from token mappings");
+// Latest samples per source; every submission aggregates from these.
+#[derive(Default)]
+struct PriceFeedState {
+    pyth_prices: HashMap<String, PriceSample>,
+    exchange_prices: Vec<(&'static str, HashMap<String, f64>)>,
+}
 
-    let timestamp_val = SystemTime::now()
+// Drops cached Pyth samples that have aged past MAX_PRICE_INTERVAL since they
+// were accepted, e.g. while the stream is reconnecting.
+fn evict_stale_pyth_prices(state: &mut PriceFeedState) {
+    let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs();
 
-    for token_val in tokens.iter() {
-        if let Some(price) = prices.get(&token_val.key) {
-            for (period_key, period_val) in PERIOD_MAP.iter() {
-                store_price_candle(
-                    db,
-                    timestamp_val,
-                    token_val.address.to_string(),
-                    *price,
-                    period_key.to_string(),
-                    *period_val,
-                )
-                .await?;
-            }
+    state.pyth_prices.retain(|token, sample| match sample.publish_time {
+        Some(publish_time) if is_stale(publish_time, now) => {
+            record_pyth_rejection(
+                token,
+                PythRejectReason::Stale,
+                &format!("cached price published {}s ago", now.saturating_sub(publish_time)),
+            );
+            false
         }
+        _ => true,
+    });
+}
+
+fn submit_feed_state(
+    builder: &mut CandleBuilder,
+    feeds: &LiveFeeds,
+    chain_id: i64,
+    state: &PriceFeedState,
+) {
+    match get_token_prices(chain_id, &state.pyth_prices, &state.exchange_prices) {
+        Ok(prices) => submit_new_prices_to_builder(builder, feeds, chain_id, prices),
+        Err(e) => eprintln!("Error aggregating prices: {}", e),
     }
+}
 
-    Ok(())
+async fn flush_candles(db: &DatabaseConnection, chain_id: i64, builder: &mut CandleBuilder) {
+    match builder.flush(db).await {
+        Ok(0) => {}
+        Ok(count) => println!("Flushed {} candles to db for chain {}", count, chain_id),
+        // The candles stay dirty and are retried on the next flush.
+        Err(e) => eprintln!("Error flushing candles for chain {}: {}", chain_id, e),
+    }
 }
 
-pub async fn submit_prices(db: &DatabaseConnection) -> Result<(), DbErr> {
+// In stream mode Pyth updates are submitted as they arrive and exchange
+// tickers are refreshed on the shortest update interval of the classes the
+// submitter prices. In poll mode both are fetched on that interval. Candles
+// are built in memory and flushed every CANDLE_FLUSH_INTERVAL.
+pub async fn submit_prices(
+    db: &DatabaseConnection,
+    chain_id: i64,
+    feeds: LiveFeeds,
+) -> Result<(), DbErr> {
+    let mut builder = CandleBuilder::load(db, chain_id).await?;
+
+    let (sender, mut receiver) = mpsc::channel(PYTH_UPDATE_CHANNEL_SIZE);
+    if ENV.PYTH_FEED_MODE == PythFeedMode::Stream {
+        tokio::spawn(stream_pyth_prices(chain_id, sender));
+    } else {
+        drop(sender);
+    }
+
+    let mut state = PriceFeedState::default();
+    let fetch_interval_ms = REGISTRY
+        .feed_interval_ms(PriceFeedKind::Submitter)
+        .unwrap_or(PRICE_FETCH_INTERVAL as u64);
+    let mut fetch_interval = interval(Duration::from_millis(fetch_interval_ms));
+    fetch_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut flush_interval = interval(Duration::from_millis(CANDLE_FLUSH_INTERVAL as u64));
+    flush_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
     loop {
-        let prices = match get_token_prices().await {
-            Ok(prices) => prices,
-            Err(e) => {
-                eprintln!("Error getting Pyth prices: {}", e);
+        tokio::select! {
+            Some(pyth_prices) = receiver.recv() => {
+                state.pyth_prices.extend(pyth_prices);
+            }
+            _ = fetch_interval.tick() => {
+                if ENV.PYTH_FEED_MODE == PythFeedMode::Poll {
+                    match get_pyth_prices(chain_id).await {
+                        Ok(pyth_prices) => state.pyth_prices.extend(pyth_prices),
+                        Err(e) => eprintln!("Error getting Pyth prices: {}", e),
+                    }
+                }
+                state.exchange_prices = get_exchange_prices(chain_id).await;
+            }
+            _ = flush_interval.tick() => {
+                flush_candles(db, chain_id, &mut builder).await;
                 continue;
             }
-        };
-        submit_new_prices_to_db(db, prices).await?;
-        println!("Submitting price to db...");
+        }
+
+        evict_stale_pyth_prices(&mut state);
+        submit_feed_state(&mut builder, &feeds, chain_id, &state);
     }
 }
-
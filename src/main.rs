@@ -1,8 +1,10 @@
 use controllers::IncentivesStip::get_dummy_data;
 use controllers::PriceCandles::get_price_candles;
+use controllers::PriceStream::get_price_stream;
 use controllers::PriceTickers::getPriceTickers;
 use controllers::Prices24h::get_price24h;
 use controllers::ReportUI::report_ui;
+use controllers::TradingViewUdf::{udf_config, udf_history, udf_search, udf_symbols, udf_time};
 use rocket::{get, launch, routes, Build, Rocket};
 use sea_orm::DatabaseConnection;
 use std::net::Ipv4Addr;
@@ -18,8 +20,15 @@ pub mod assets;
 pub mod data;
 pub mod controllers;
 
+#[path = "../entity/src/mod.rs"]
+pub mod entities;
 
-use crate::jobs::index::executejobs;
+
+use crate::assets::realestate::feed::realestatefeed::resolve_parcl_names;
+use crate::configs::envconfig::ENV;
+use crate::jobs::index::{executejobs, LiveFeeds};
+use crate::utils::eventsutils::PriceEvents;
+use crate::utils::latestpricesutils::LatestPriceCache;
 
 pub struct DbConnection(pub DatabaseConnection);
 
@@ -44,7 +53,19 @@ async fn rocket() -> Rocket<Build> {
 
     // dotenv().ok(); 
 
-    tokio::spawn(executejobs());
+    if env::args().nth(1).as_deref() == Some("resolve-parcl-ids") {
+        print_parcl_ids().await;
+        std::process::exit(0);
+    }
+
+    // Submitters publish live updates that /prices/stream pushes to clients
+    // and keep the latest prices the tickers are served from
+    let price_events = PriceEvents::new();
+    let latest_prices = LatestPriceCache::new();
+    tokio::spawn(executejobs(LiveFeeds {
+        events: price_events.clone(),
+        latest_prices: latest_prices.clone(),
+    }));
 
     let port = 8000;
     print_network_info(port);
@@ -54,12 +75,32 @@ async fn rocket() -> Rocket<Build> {
     // let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     // let db = Database::connect(&db_url).await.unwrap();
 
-    rocket::build()
+    mount_routes(rocket::build())
         .manage(db)
+        .manage(price_events)
+        .manage(latest_prices)
+}
+
+fn mount_routes(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket
         .mount("/", routes![hello, get_dummy_data, report_ui])
-        .mount("/prices", routes![get_price_candles, get_price24h, getPriceTickers])
+        .mount("/prices", routes![get_price_candles, get_price24h, getPriceTickers, get_price_stream])
+        .mount("/udf", routes![udf_config, udf_symbols, udf_search, udf_history, udf_time])
         // .mount("/candles", routes![get_price_candles])
-        // .mount("/prices/24h", routes![get_price24h])
+}
+
+// Prints `<symbol> <parcl_id>` for every real-estate asset whose `parcl_name`
+// matches a Parcl market, for pinning as `parcl_id` in the registry.
+async fn print_parcl_ids() {
+    let client = reqwest::Client::new();
+    match resolve_parcl_names(&client, &ENV.PARCL_BASE_URL).await {
+        Ok(resolved) => {
+            for (symbol, parcl_id) in resolved {
+                println!("{} {}", symbol, parcl_id);
+            }
+        }
+        Err(e) => eprintln!("Error fetching Parcl ids: {}", e),
+    }
 }
 
 fn print_network_info(port: u16) {
@@ -80,4 +121,43 @@ fn get_local_ip() -> std::io::Result<Option<Ipv4Addr>> {
         std::net::IpAddr::V4(ip) => Ok(Some(ip)),
         _ => Ok(None),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Status;
+    use rocket::local::asynchronous::Client;
+
+    async fn client() -> Client {
+        let rocket = mount_routes(rocket::build())
+            .manage(DbConnection(DatabaseConnection::Disconnected))
+            .manage(PriceEvents::new())
+            .manage(LatestPriceCache::new());
+        // Igniting fails on colliding routes.
+        match Client::untracked(rocket).await {
+            Ok(client) => client,
+            Err(e) => panic!("Failed to ignite rocket: {}", e),
+        }
+    }
+
+    // /prices/candles serves candles when `period` and `token_symbol` are
+    // given and the 24h stats otherwise.
+    #[rocket::async_test]
+    async fn candles_and_24h_share_their_path() {
+        let client = client().await;
+
+        let response = client
+            .get("/prices/candles?period=2h&token_symbol=BTC")
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let body = response.into_string().await.unwrap_or_default();
+        assert!(body.contains("Unsupported period"), "{}", body);
+
+        let response = client.get("/prices/candles?chainId=1").dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let body = response.into_string().await.unwrap_or_default();
+        assert!(body.contains("Unsupported chainId"), "{}", body);
+    }
+}
@@ -0,0 +1,327 @@
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// How the per-source samples of one token are combined into a single price.
+// Every strategy is a pure function of its inputs, so the same samples always
+// produce the same price.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationStrategy {
+    Median,
+    // Drops the highest and lowest sample when there are more than two, then
+    // takes the plain mean.
+    #[default]
+    TrimmedMean,
+    // Weighted mean using `SOURCE_WEIGHTS` from env.json (default weight 1).
+    SourceWeighted,
+    // Inverse-variance weighting by Pyth's `conf`. Samples without a
+    // confidence (the exchanges) get the mean weight of those with one, so
+    // they still count; falls back to median if none have one.
+    ConfidenceWeighted,
+}
+
+impl AggregationStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggregationStrategy::Median => "median",
+            AggregationStrategy::TrimmedMean => "trimmed_mean",
+            AggregationStrategy::SourceWeighted => "source_weighted",
+            AggregationStrategy::ConfidenceWeighted => "confidence_weighted",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PriceSample {
+    pub source: &'static str,
+    pub price: f64,
+    pub conf: Option<f64>,
+    // Unix seconds the source published the price, when it reports one.
+    pub publish_time: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AggregatedPrice {
+    pub price: f64,
+    // The strategy actually applied, which differs from the configured one
+    // when confidence weighting falls back to median.
+    pub strategy: AggregationStrategy,
+    // Sorted names of the sources that contributed to `price`.
+    pub sources: Vec<&'static str>,
+    // Half-width of the min/max band around `price`, set from the asset's
+    // spread policy after aggregation.
+    pub spread: f64,
+}
+
+impl AggregatedPrice {
+    pub fn sources_label(&self) -> String {
+        self.sources.join(",")
+    }
+
+    // `price` as the decimal stored in candles, limited to the significant
+    // digits an f64 actually carries.
+    pub fn decimal_price(&self) -> Option<Decimal> {
+        Decimal::from_f64(self.price).map(|price| price.normalize())
+    }
+
+    pub fn decimal_spread(&self) -> Option<Decimal> {
+        Decimal::from_f64(self.spread).map(|spread| spread.normalize())
+    }
+}
+
+fn sorted_by_price(samples: &[PriceSample]) -> Vec<&PriceSample> {
+    let mut sorted: Vec<&PriceSample> = samples.iter().collect();
+    sorted.sort_by(|a, b| a.price.total_cmp(&b.price).then(a.source.cmp(b.source)));
+    sorted
+}
+
+fn source_names(samples: &[&PriceSample]) -> Vec<&'static str> {
+    let mut sources: Vec<&'static str> = samples.iter().map(|sample| sample.source).collect();
+    sources.sort();
+    sources.dedup();
+    sources
+}
+
+fn weighted_mean(samples: &[(&PriceSample, f64)]) -> Option<f64> {
+    let weight_sum: f64 = samples.iter().map(|(_, weight)| weight).sum();
+    if weight_sum <= 0.0 {
+        return None;
+    }
+    let price_sum: f64 = samples.iter().map(|(sample, weight)| sample.price * weight).sum();
+    Some(price_sum / weight_sum)
+}
+
+fn median(samples: &[PriceSample]) -> Option<AggregatedPrice> {
+    let sorted = sorted_by_price(samples);
+    let middle = sorted.len() / 2;
+    let contributing = match sorted.len() {
+        0 => return None,
+        len if len.is_multiple_of(2) => vec![sorted[middle - 1], sorted[middle]],
+        _ => vec![sorted[middle]],
+    };
+    let price = contributing.iter().map(|sample| sample.price).sum::<f64>()
+        / contributing.len() as f64;
+
+    Some(AggregatedPrice {
+        price,
+        strategy: AggregationStrategy::Median,
+        sources: source_names(&contributing),
+        spread: 0.0,
+    })
+}
+
+fn trimmed_mean(samples: &[PriceSample]) -> Option<AggregatedPrice> {
+    let mut sorted = sorted_by_price(samples);
+    if sorted.len() > 2 {
+        sorted.pop();
+        sorted.remove(0);
+    }
+
+    let weighted: Vec<(&PriceSample, f64)> = sorted.iter().map(|sample| (*sample, 1.0)).collect();
+    Some(AggregatedPrice {
+        price: weighted_mean(&weighted)?,
+        strategy: AggregationStrategy::TrimmedMean,
+        sources: source_names(&sorted),
+        spread: 0.0,
+    })
+}
+
+fn source_weighted(
+    samples: &[PriceSample],
+    source_weights: &HashMap<String, f64>,
+) -> Option<AggregatedPrice> {
+    let weighted: Vec<(&PriceSample, f64)> = sorted_by_price(samples)
+        .into_iter()
+        .map(|sample| (sample, *source_weights.get(sample.source).unwrap_or(&1.0)))
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+    let contributing: Vec<&PriceSample> = weighted.iter().map(|(sample, _)| *sample).collect();
+
+    Some(AggregatedPrice {
+        price: weighted_mean(&weighted)?,
+        strategy: AggregationStrategy::SourceWeighted,
+        sources: source_names(&contributing),
+        spread: 0.0,
+    })
+}
+
+fn confidence_weight(sample: &PriceSample) -> Option<f64> {
+    match sample.conf {
+        Some(conf) if conf > 0.0 => Some(1.0 / (conf * conf)),
+        _ => None,
+    }
+}
+
+fn confidence_weighted(samples: &[PriceSample]) -> Option<AggregatedPrice> {
+    let known_weights: Vec<f64> = samples.iter().filter_map(confidence_weight).collect();
+    if known_weights.is_empty() {
+        return median(samples);
+    }
+    let default_weight = known_weights.iter().sum::<f64>() / known_weights.len() as f64;
+
+    let weighted: Vec<(&PriceSample, f64)> = sorted_by_price(samples)
+        .into_iter()
+        .map(|sample| (sample, confidence_weight(sample).unwrap_or(default_weight)))
+        .collect();
+    let contributing: Vec<&PriceSample> = weighted.iter().map(|(sample, _)| *sample).collect();
+
+    Some(AggregatedPrice {
+        price: weighted_mean(&weighted)?,
+        strategy: AggregationStrategy::ConfidenceWeighted,
+        sources: source_names(&contributing),
+        spread: 0.0,
+    })
+}
+
+// Returns None when there is nothing to aggregate.
+pub fn aggregate_prices(
+    strategy: AggregationStrategy,
+    samples: &[PriceSample],
+    source_weights: &HashMap<String, f64>,
+) -> Option<AggregatedPrice> {
+    let samples: Vec<PriceSample> = samples
+        .iter()
+        .filter(|sample| sample.price.is_finite() && sample.price > 0.0)
+        .cloned()
+        .collect();
+
+    if samples.is_empty() {
+        return None;
+    }
+
+    match strategy {
+        AggregationStrategy::Median => median(&samples),
+        AggregationStrategy::TrimmedMean => trimmed_mean(&samples),
+        AggregationStrategy::SourceWeighted => source_weighted(&samples, source_weights),
+        AggregationStrategy::ConfidenceWeighted => confidence_weighted(&samples),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(source: &'static str, price: f64, conf: Option<f64>) -> PriceSample {
+        PriceSample {
+            source,
+            price,
+            conf,
+            publish_time: None,
+        }
+    }
+
+    fn exchange_samples() -> Vec<PriceSample> {
+        vec![
+            sample("binance", 101.0, None),
+            sample("okx", 99.0, None),
+            sample("kraken", 100.0, None),
+            sample("bybit", 130.0, None),
+        ]
+    }
+
+    fn aggregate(strategy: AggregationStrategy, samples: &[PriceSample]) -> AggregatedPrice {
+        aggregate_prices(strategy, samples, &HashMap::new()).unwrap()
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        let samples = exchange_samples();
+        let aggregated = aggregate(AggregationStrategy::Median, &samples);
+        assert_eq!(aggregated.price, 100.5);
+        assert_eq!(aggregated.sources, vec!["binance", "kraken"]);
+
+        let aggregated = aggregate(AggregationStrategy::Median, &samples[..3]);
+        assert_eq!(aggregated.price, 100.0);
+        assert_eq!(aggregated.sources, vec!["kraken"]);
+    }
+
+    #[test]
+    fn trimmed_mean_drops_extremes() {
+        let aggregated = aggregate(AggregationStrategy::TrimmedMean, &exchange_samples());
+        assert_eq!(aggregated.price, 100.5);
+        assert_eq!(aggregated.strategy, AggregationStrategy::TrimmedMean);
+        assert_eq!(aggregated.sources, vec!["binance", "kraken"]);
+
+        // Two samples are averaged as they are.
+        let aggregated = aggregate(AggregationStrategy::TrimmedMean, &exchange_samples()[..2]);
+        assert_eq!(aggregated.price, 100.0);
+    }
+
+    #[test]
+    fn source_weighted_uses_configured_weights() {
+        let source_weights = HashMap::from([
+            ("binance".to_string(), 3.0),
+            ("bybit".to_string(), 0.0),
+        ]);
+        let aggregated = aggregate_prices(
+            AggregationStrategy::SourceWeighted,
+            &exchange_samples(),
+            &source_weights,
+        )
+        .unwrap();
+        // (101 * 3 + 99 + 100) / 5, bybit is weighted out.
+        assert_eq!(aggregated.price, 100.4);
+        assert_eq!(aggregated.sources, vec!["binance", "kraken", "okx"]);
+    }
+
+    #[test]
+    fn confidence_weighted_by_inverse_variance() {
+        let samples = vec![sample("pyth", 100.0, Some(1.0)), sample("pyth_ema", 110.0, Some(2.0))];
+        let aggregated = aggregate(AggregationStrategy::ConfidenceWeighted, &samples);
+        // Weights 1 and 1/4.
+        assert_eq!(aggregated.price, 102.0);
+        assert_eq!(aggregated.strategy, AggregationStrategy::ConfidenceWeighted);
+    }
+
+    #[test]
+    fn confidence_weighted_keeps_samples_without_conf() {
+        let samples = vec![
+            sample("pyth", 100.0, Some(0.5)),
+            sample("binance", 104.0, None),
+            sample("okx", 102.0, None),
+        ];
+        let aggregated = aggregate(AggregationStrategy::ConfidenceWeighted, &samples);
+        // All three get pyth's weight of 4.
+        assert_eq!(aggregated.price, 102.0);
+        assert_eq!(aggregated.sources, vec!["binance", "okx", "pyth"]);
+    }
+
+    #[test]
+    fn confidence_weighted_falls_back_to_median() {
+        let aggregated = aggregate(AggregationStrategy::ConfidenceWeighted, &exchange_samples());
+        assert_eq!(aggregated.price, 100.5);
+        assert_eq!(aggregated.strategy, AggregationStrategy::Median);
+    }
+
+    #[test]
+    fn invalid_prices_are_dropped() {
+        let samples = vec![
+            sample("binance", f64::NAN, None),
+            sample("okx", 0.0, None),
+            sample("kraken", -1.0, None),
+        ];
+        assert!(aggregate_prices(AggregationStrategy::Median, &samples, &HashMap::new()).is_none());
+
+        let samples = vec![sample("binance", f64::INFINITY, None), sample("okx", 99.0, None)];
+        assert_eq!(aggregate(AggregationStrategy::TrimmedMean, &samples).price, 99.0);
+    }
+
+    #[test]
+    fn same_samples_same_price_in_any_order() {
+        let samples = exchange_samples();
+        let mut reversed = samples.clone();
+        reversed.reverse();
+        for strategy in [
+            AggregationStrategy::Median,
+            AggregationStrategy::TrimmedMean,
+            AggregationStrategy::SourceWeighted,
+        ] {
+            let aggregated = aggregate(strategy, &samples);
+            let aggregated_reversed = aggregate(strategy, &reversed);
+            assert_eq!(aggregated.price.to_bits(), aggregated_reversed.price.to_bits());
+            assert_eq!(aggregated.sources, aggregated_reversed.sources);
+        }
+    }
+}
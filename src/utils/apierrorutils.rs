@@ -0,0 +1,99 @@
+use rocket::http::Status;
+use rocket::response::{self, status::Custom, Responder};
+use rocket::serde::json::Json;
+use rocket::Request;
+use sea_orm::DbErr;
+use serde_json::json;
+use std::fmt;
+
+// Errors returned by the API. Every variant maps to one HTTP status and one
+// stable `code`; the body is `{"error": <message>, "code": <code>}`.
+#[derive(Debug)]
+pub enum ApiError {
+    UnsupportedChain,
+    UnsupportedToken(String),
+    UnsupportedPeriod(String),
+    InvalidParameter(String),
+    NotFound(String),
+    // Database reachable but the query failed.
+    Database(DbErr),
+    // Database or another dependency could not be reached.
+    Unavailable(String),
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> Status {
+        match self {
+            ApiError::UnsupportedChain
+            | ApiError::UnsupportedToken(_)
+            | ApiError::UnsupportedPeriod(_)
+            | ApiError::InvalidParameter(_) => Status::BadRequest,
+            ApiError::NotFound(_) => Status::NotFound,
+            ApiError::Unavailable(_) => Status::ServiceUnavailable,
+            ApiError::Database(_) | ApiError::Internal(_) => Status::InternalServerError,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::UnsupportedChain => "unsupported_chain",
+            ApiError::UnsupportedToken(_) => "unsupported_token",
+            ApiError::UnsupportedPeriod(_) => "unsupported_period",
+            ApiError::InvalidParameter(_) => "invalid_parameter",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Database(_) => "database_error",
+            ApiError::Unavailable(_) => "service_unavailable",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    // Client-facing message; database and internal details are only logged.
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::UnsupportedChain => "Unsupported chainId".to_string(),
+            ApiError::UnsupportedToken(message)
+            | ApiError::UnsupportedPeriod(message)
+            | ApiError::InvalidParameter(message)
+            | ApiError::NotFound(message)
+            | ApiError::Unavailable(message) => message.clone(),
+            ApiError::Database(_) => "Database query failed".to_string(),
+            ApiError::Internal(_) => "Internal error".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Database(e) => write!(f, "{}: {}", self.code(), e),
+            ApiError::Internal(detail) => write!(f, "{}: {}", self.code(), detail),
+            _ => write!(f, "{}: {}", self.code(), self.message()),
+        }
+    }
+}
+
+impl From<DbErr> for ApiError {
+    fn from(e: DbErr) -> Self {
+        match e {
+            DbErr::ConnectionAcquire(_) | DbErr::Conn(_) => {
+                eprintln!("Database unavailable: {}", e);
+                ApiError::Unavailable("Database unavailable".to_string())
+            }
+            e => ApiError::Database(e),
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        if self.status().code >= 500 {
+            eprintln!("{} {} failed: {}", request.method(), request.uri(), self);
+        }
+        Custom(
+            self.status(),
+            Json(json!({ "error": self.message(), "code": self.code() })),
+        )
+        .respond_to(request)
+    }
+}
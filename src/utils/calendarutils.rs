@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+
+use chrono::{Datelike, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::configs::envconfig::ENV;
+use crate::configs::registryconfig::{AssetRecord, REGISTRY};
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+const MINUTES_PER_WEEK: i64 = 7 * MINUTES_PER_DAY;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+// How far back last_open_at looks for a session.
+const MAX_CLOSED_DAYS: i64 = 14;
+
+#[derive(Deserialize, Debug)]
+struct SessionConfig {
+    open: String,
+    close: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ScheduleConfig {
+    #[serde(default)]
+    sessions: Vec<SessionConfig>,
+    #[serde(default)]
+    holidays: Vec<NaiveDate>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CalendarConfig {
+    schedules: HashMap<String, ScheduleConfig>,
+}
+
+// Open from `open` until `close`, in minutes since Monday 00:00 UTC. A session
+// with close before open wraps over the end of the week (e.g. Sunday 22:00 to
+// Friday 22:00).
+#[derive(Debug, Clone, Copy)]
+struct WeeklySession {
+    open: i64,
+    close: i64,
+}
+
+impl WeeklySession {
+    fn contains(&self, minute_of_week: i64) -> bool {
+        if self.open < self.close {
+            self.open <= minute_of_week && minute_of_week < self.close
+        } else {
+            minute_of_week >= self.open || minute_of_week < self.close
+        }
+    }
+
+    // The session as ranges that do not wrap.
+    fn ranges(&self) -> Vec<(i64, i64)> {
+        if self.open < self.close {
+            vec![(self.open, self.close)]
+        } else {
+            vec![(self.open, MINUTES_PER_WEEK), (0, self.close)]
+        }
+    }
+}
+
+// UTC weekly sessions plus whole-day (UTC) holidays. No sessions means open
+// around the clock except on holidays.
+#[derive(Debug)]
+pub struct TradingSchedule {
+    sessions: Vec<WeeklySession>,
+    holidays: Vec<NaiveDate>,
+}
+
+impl TradingSchedule {
+    fn is_holiday(&self, timestamp: i64) -> bool {
+        Utc.timestamp_opt(timestamp, 0)
+            .single()
+            .is_some_and(|time| self.holidays.contains(&time.date_naive()))
+    }
+
+    pub fn is_open(&self, timestamp: i64) -> bool {
+        if self.is_holiday(timestamp) {
+            return false;
+        }
+        self.sessions.is_empty()
+            || self
+                .sessions
+                .iter()
+                .any(|session| session.contains(minute_of_week(timestamp)))
+    }
+
+    // `timestamp` if the market is open then, otherwise the end of the latest
+    // session before it. None if there was none in the last MAX_CLOSED_DAYS.
+    pub fn last_open_at(&self, timestamp: i64) -> Option<i64> {
+        if self.is_open(timestamp) {
+            return Some(timestamp);
+        }
+
+        let today = timestamp.div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY;
+        for days_back in 0..=MAX_CLOSED_DAYS {
+            let day_start = today - days_back * SECONDS_PER_DAY;
+            if self.is_holiday(day_start) {
+                continue;
+            }
+            let day_end = if days_back == 0 { timestamp } else { day_start + SECONDS_PER_DAY };
+            if self.sessions.is_empty() {
+                return Some(day_end);
+            }
+
+            let day_first_minute = minute_of_week(day_start);
+            let day_last_minute = day_first_minute + (day_end - day_start) / 60;
+            let session_end = self
+                .sessions
+                .iter()
+                .flat_map(|session| session.ranges())
+                .filter_map(|(open, close)| {
+                    let end = close.min(day_last_minute);
+                    (end > open.max(day_first_minute)).then_some(end)
+                })
+                .max();
+            if let Some(end) = session_end {
+                return Some(day_start + (end - day_first_minute) * 60);
+            }
+        }
+        None
+    }
+
+    // The sessions as a TradingView UDF `session` string, e.g.
+    // "2200-2200:23456" for Sunday 22:00 to Friday 22:00. Each session is cut
+    // into days at its open time; a piece running past midnight belongs to the
+    // day it closes on, as TradingView expects for overnight sessions. Days
+    // are 1 (Sunday) to 7 (Saturday).
+    pub fn udf_session(&self) -> String {
+        if self.sessions.is_empty() {
+            return "24x7".to_string();
+        }
+
+        // "HHMM-HHMM" -> days it applies to
+        let mut pieces: BTreeMap<String, BTreeSet<i64>> = BTreeMap::new();
+        for session in &self.sessions {
+            let close = if session.close > session.open {
+                session.close
+            } else {
+                session.close + MINUTES_PER_WEEK
+            };
+            let mut start = session.open;
+            while start < close {
+                let end = (start + MINUTES_PER_DAY).min(close);
+                let overnight =
+                    end / MINUTES_PER_DAY != start / MINUTES_PER_DAY && end % MINUTES_PER_DAY != 0;
+                let day = if overnight { end } else { start } / MINUTES_PER_DAY;
+                let range = format!(
+                    "{}-{}",
+                    format_minute_of_day(start),
+                    format_minute_of_day(end)
+                );
+                // Monday is day 0 here and 2 in TradingView.
+                pieces.entry(range).or_default().insert((day + 1) % 7 + 1);
+                start = end;
+            }
+        }
+
+        pieces
+            .iter()
+            .map(|(range, days)| {
+                let days: String = days.iter().map(|day| day.to_string()).collect();
+                format!("{}:{}", range, days)
+            })
+            .collect::<Vec<String>>()
+            .join("|")
+    }
+
+    // The holidays as a TradingView UDF `session_holidays` string
+    // ("YYYYMMDD,YYYYMMDD").
+    pub fn udf_holidays(&self) -> String {
+        self.holidays
+            .iter()
+            .map(|date| date.format("%Y%m%d").to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+fn format_minute_of_day(minute: i64) -> String {
+    let minute = minute.rem_euclid(MINUTES_PER_DAY);
+    format!("{:02}{:02}", minute / 60, minute % 60)
+}
+
+fn minute_of_week(timestamp: i64) -> i64 {
+    let Some(time) = Utc.timestamp_opt(timestamp, 0).single() else {
+        return 0;
+    };
+    time.weekday().num_days_from_monday() as i64 * MINUTES_PER_DAY
+        + time.hour() as i64 * 60
+        + time.minute() as i64
+}
+
+// Parses "sun 22:00" into minutes since Monday 00:00.
+fn parse_session_time(value: &str) -> Option<i64> {
+    let (day, time) = value.trim().split_once(' ')?;
+    let weekday: Weekday = day.parse().ok()?;
+    let (hour, minute) = time.trim().split_once(':')?;
+    let (hour, minute): (i64, i64) = (hour.parse().ok()?, minute.parse().ok()?);
+    if !(0..24).contains(&hour) || !(0..60).contains(&minute) {
+        return None;
+    }
+    Some(weekday.num_days_from_monday() as i64 * MINUTES_PER_DAY + hour * 60 + minute)
+}
+
+#[derive(Debug)]
+pub struct TradingCalendar {
+    schedules: HashMap<String, TradingSchedule>,
+}
+
+impl TradingCalendar {
+    // An asset trades on its own `trading_calendar` if set, else on its asset
+    // class's `trading_hours`.
+    pub fn for_asset(&self, asset: &AssetRecord) -> &TradingSchedule {
+        let name = asset
+            .trading_calendar
+            .as_ref()
+            .unwrap_or(&REGISTRY.class_config(asset.asset_class).trading_hours);
+        &self.schedules[name]
+    }
+
+    // Assets missing from the registry are treated as always open.
+    pub fn is_open(&self, chain_id: i64, symbol: &str, timestamp: i64) -> bool {
+        REGISTRY
+            .asset(chain_id, symbol)
+            .is_none_or(|asset| self.for_asset(asset).is_open(timestamp))
+    }
+}
+
+pub static TRADING_CALENDAR: Lazy<TradingCalendar> = Lazy::new(|| {
+    let calendar_content =
+        fs::read_to_string(&ENV.CALENDAR_PATH).expect("Failed to read trading calendar");
+    let config: CalendarConfig =
+        serde_json::from_str(&calendar_content).expect("Failed to parse trading calendar");
+
+    let schedules: HashMap<String, TradingSchedule> = config
+        .schedules
+        .into_iter()
+        .map(|(name, schedule)| {
+            let sessions = schedule
+                .sessions
+                .iter()
+                .map(|session| {
+                    match (parse_session_time(&session.open), parse_session_time(&session.close)) {
+                        (Some(open), Some(close)) => WeeklySession { open, close },
+                        _ => panic!(
+                            "Invalid session {} - {} in trading schedule {}",
+                            session.open, session.close, name
+                        ),
+                    }
+                })
+                .collect();
+            let trading_schedule = TradingSchedule {
+                sessions,
+                holidays: schedule.holidays,
+            };
+            (name, trading_schedule)
+        })
+        .collect();
+
+    // Every schedule the registry refers to must exist.
+    let class_schedules = REGISTRY
+        .asset_classes
+        .values()
+        .map(|config| &config.trading_hours);
+    let asset_schedules = REGISTRY
+        .networks
+        .values()
+        .flat_map(|network| network.assets.iter())
+        .filter_map(|asset| asset.trading_calendar.as_ref());
+    for name in class_schedules.chain(asset_schedules) {
+        if !schedules.contains_key(name) {
+            panic!("Trading schedule {} not found in trading calendar", name);
+        }
+    }
+
+    TradingCalendar { schedules }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(sessions: &[(&str, &str)]) -> TradingSchedule {
+        let sessions = sessions
+            .iter()
+            .map(|(open, close)| WeeklySession {
+                open: parse_session_time(open).unwrap(),
+                close: parse_session_time(close).unwrap(),
+            })
+            .collect();
+        TradingSchedule {
+            sessions,
+            holidays: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn udf_session_follows_the_calendar() {
+        assert_eq!(TRADING_CALENDAR.schedules["always"].udf_session(), "24x7");
+        assert_eq!(TRADING_CALENDAR.schedules["weekdays"].udf_session(), "2200-2200:23456");
+        assert_eq!(
+            TRADING_CALENDAR.schedules["us_treasuries"].udf_holidays().get(..17),
+            Some("20260101,20260119")
+        );
+        assert_eq!(TRADING_CALENDAR.schedules["always"].udf_holidays(), "");
+    }
+
+    #[test]
+    fn udf_session_splits_sessions_into_days() {
+        let regular_hours = [
+            ("mon 14:30", "mon 21:00"),
+            ("tue 14:30", "tue 21:00"),
+            ("wed 14:30", "wed 21:00"),
+            ("thu 14:30", "thu 21:00"),
+            ("fri 14:30", "fri 21:00"),
+            ("sat 10:00", "sat 12:00"),
+        ];
+        assert_eq!(
+            schedule(&regular_hours).udf_session(),
+            "1000-1200:7|1430-2100:23456"
+        );
+
+        // A session ending at midnight stays on its own day; a shorter last
+        // piece keeps its close.
+        let sessions = [("mon 00:00", "wed 00:00"), ("fri 18:00", "sat 02:00")];
+        assert_eq!(
+            schedule(&sessions).udf_session(),
+            "0000-0000:23|1800-0200:7"
+        );
+        let sessions = [("mon 00:00", "tue 06:00")];
+        assert_eq!(schedule(&sessions).udf_session(), "0000-0000:2|0000-0600:3");
+    }
+}
@@ -0,0 +1,561 @@
+use crate::entities::{prelude::*, *};
+use crate::utils::apierrorutils::ApiError;
+use crate::utils::helpersutils::PERIOD_MAP;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    DatabaseConnection, DbErr, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// One OHLC bar as served by the API, either stored or resampled.
+#[derive(Debug, Clone, Serialize)]
+pub struct CandleBar {
+    pub timestamp: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+}
+
+impl From<price_candle::Model> for CandleBar {
+    fn from(candle: price_candle::Model) -> Self {
+        CandleBar {
+            timestamp: candle.timestamp,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+        }
+    }
+}
+
+// Bounds on candle timestamps: `from` and `to` are inclusive, `before` and
+// `after` are exclusive and come from a pagination cursor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CandleRange {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub before: Option<i64>,
+    pub after: Option<i64>,
+}
+
+impl CandleRange {
+    // Inclusive lower bound, if any.
+    fn lower(&self) -> Option<i64> {
+        let after = self.after.map(|after| after.saturating_add(1));
+        match (self.from, after) {
+            (Some(from), Some(after)) => Some(from.max(after)),
+            (from, after) => from.or(after),
+        }
+    }
+
+    // Exclusive upper bound, if any.
+    fn upper(&self) -> Option<i64> {
+        let to = self.to.map(|to| to.saturating_add(1));
+        match (to, self.before) {
+            (Some(to), Some(before)) => Some(to.min(before)),
+            (to, before) => to.or(before),
+        }
+    }
+}
+
+// Which end of a range a limited candle query keeps, and the order the
+// candles are returned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandleOrder {
+    NewestFirst,
+    OldestFirst,
+}
+
+impl CandleOrder {
+    fn sql_order(&self) -> Order {
+        match self {
+            CandleOrder::NewestFirst => Order::Desc,
+            CandleOrder::OldestFirst => Order::Asc,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CandleOrder::NewestFirst => "desc",
+            CandleOrder::OldestFirst => "asc",
+        }
+    }
+
+    // The range of the page after the one that ended at `cursor`.
+    pub fn page_after(&self, range: CandleRange, cursor: i64) -> CandleRange {
+        match self {
+            CandleOrder::NewestFirst => CandleRange { before: Some(cursor), ..range },
+            CandleOrder::OldestFirst => CandleRange { after: Some(cursor), ..range },
+        }
+    }
+}
+
+// Cursors are the hex encoding of "<token>:<period>:<order>:<timestamp>",
+// where timestamp is the last candle of the previous page. Clients treat them
+// as opaque.
+pub fn encode_cursor(
+    token_symbol: &str,
+    period: &str,
+    order: CandleOrder,
+    timestamp: i64,
+) -> String {
+    format!("{}:{}:{}:{}", token_symbol, period, order.name(), timestamp)
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Returns the timestamp of a cursor issued for the same token, period and
+// order.
+pub fn decode_cursor(
+    cursor: &str,
+    token_symbol: &str,
+    period: &str,
+    order: CandleOrder,
+) -> Option<i64> {
+    if !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+        return None;
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let decoded = String::from_utf8(bytes).ok()?;
+
+    let (prefix, timestamp) = decoded.rsplit_once(':')?;
+    if prefix != format!("{}:{}:{}", token_symbol, period, order.name()) {
+        return None;
+    }
+    timestamp.parse().ok()
+}
+
+// Periods whose buckets tile `period` exactly, coarsest first.
+fn finer_periods(period: &str) -> Vec<(&'static str, u64)> {
+    let interval = match PERIOD_MAP.get(period) {
+        Some(interval) => *interval,
+        None => return Vec::new(),
+    };
+    let mut periods: Vec<(&'static str, u64)> = PERIOD_MAP
+        .iter()
+        .filter(|(_, finer)| **finer < interval && interval.is_multiple_of(**finer))
+        .map(|(name, finer)| (*name, *finer))
+        .collect();
+    periods.sort_by_key(|(_, finer)| std::cmp::Reverse(*finer));
+    periods
+}
+
+// Newest (or oldest) `limit` candles of `token` for `period` within `range`,
+// in `order`.
+pub async fn get_candles(
+    db: &DatabaseConnection,
+    chain_id: i64,
+    token: &str,
+    period: &str,
+    range: &CandleRange,
+    order: CandleOrder,
+    limit: u64,
+) -> Result<Vec<CandleBar>, ApiError> {
+    if let (Some(from), Some(to)) = (range.from, range.to)
+        && from > to
+    {
+        return Err(ApiError::InvalidParameter("from must not be after to".to_string()));
+    }
+
+    let mut query = PriceCandle::find()
+        .filter(price_candle::Column::ChainId.eq(chain_id))
+        .filter(price_candle::Column::Token.eq(token))
+        .filter(price_candle::Column::Period.eq(period));
+    if let Some(lower) = range.lower() {
+        query = query.filter(price_candle::Column::Timestamp.gte(lower));
+    }
+    if let Some(upper) = range.upper() {
+        query = query.filter(price_candle::Column::Timestamp.lt(upper));
+    }
+
+    let stored = query
+        .order_by(price_candle::Column::Timestamp, order.sql_order())
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    if !stored.is_empty() {
+        return Ok(stored.into_iter().map(CandleBar::from).collect());
+    }
+
+    // Only resample when the period has no stored candles at all, not just
+    // none in this range.
+    let has_stored = PriceCandle::find()
+        .filter(price_candle::Column::ChainId.eq(chain_id))
+        .filter(price_candle::Column::Token.eq(token))
+        .filter(price_candle::Column::Period.eq(period))
+        .one(db)
+        .await?
+        .is_some();
+    if has_stored {
+        return Ok(Vec::new());
+    }
+
+    Ok(resample_candles(db, chain_id, token, period, range, order, limit).await?)
+}
+
+// Builds `period` candles from the coarsest finer period that has data. The
+// window ends at the `limit`-th newest (or oldest) bucket that has finer
+// candles, so gaps in the finer data don't shorten the page, and every
+// returned bar is made of all the finer candles stored for it.
+pub async fn resample_candles(
+    db: &DatabaseConnection,
+    chain_id: i64,
+    token: &str,
+    period: &str,
+    range: &CandleRange,
+    order: CandleOrder,
+    limit: u64,
+) -> Result<Vec<CandleBar>, DbErr> {
+    let interval = match PERIOD_MAP.get(period) {
+        Some(interval) => *interval as i64,
+        None => return Ok(Vec::new()),
+    };
+
+    // Range bounds moved to the finer candles: a bar is in range when its
+    // bucket is, so the bounds are rounded out to whole buckets.
+    let round_up = |timestamp: i64| (timestamp + interval - 1).div_euclid(interval) * interval;
+    let lower = range.lower().map(round_up);
+    let upper = range.upper().map(round_up);
+
+    for (finer_period, _) in finer_periods(period) {
+        let mut finer_query = PriceCandle::find()
+            .filter(price_candle::Column::ChainId.eq(chain_id))
+            .filter(price_candle::Column::Token.eq(token))
+            .filter(price_candle::Column::Period.eq(finer_period));
+        if let Some(lower) = lower {
+            finer_query = finer_query.filter(price_candle::Column::Timestamp.gte(lower));
+        }
+        if let Some(upper) = upper {
+            finer_query = finer_query.filter(price_candle::Column::Timestamp.lt(upper));
+        }
+
+        let bucket = Expr::col(price_candle::Column::Timestamp)
+            .div(interval)
+            .mul(interval);
+        let buckets: Vec<i64> = finer_query
+            .clone()
+            .select_only()
+            .column_as(bucket, "bucket")
+            .group_by(Expr::cust("bucket"))
+            .order_by(Expr::cust("bucket"), order.sql_order())
+            .limit(limit)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let window_bucket = match buckets.last() {
+            Some(window_bucket) => *window_bucket,
+            None => continue,
+        };
+        finer_query = match order {
+            CandleOrder::NewestFirst => {
+                finer_query.filter(price_candle::Column::Timestamp.gte(window_bucket))
+            }
+            CandleOrder::OldestFirst => {
+                finer_query.filter(price_candle::Column::Timestamp.lt(window_bucket + interval))
+            }
+        };
+
+        let finer_candles = finer_query
+            .order_by_asc(price_candle::Column::Timestamp)
+            .all(db)
+            .await?;
+
+        let bars = aggregate_bars(finer_candles, interval);
+        return Ok(match order {
+            CandleOrder::NewestFirst => bars.into_iter().rev().take(limit as usize).collect(),
+            CandleOrder::OldestFirst => bars.into_iter().take(limit as usize).collect(),
+        });
+    }
+
+    Ok(Vec::new())
+}
+
+// Merges candles sorted by ascending timestamp into `interval` buckets.
+pub fn aggregate_bars(candles: Vec<price_candle::Model>, interval: i64) -> Vec<CandleBar> {
+    let mut buckets: BTreeMap<i64, CandleBar> = BTreeMap::new();
+
+    for candle in candles {
+        let bucket = (candle.timestamp / interval) * interval;
+        buckets
+            .entry(bucket)
+            .and_modify(|bar| {
+                bar.high = bar.high.max(candle.high);
+                bar.low = bar.low.min(candle.low);
+                bar.close = candle.close;
+            })
+            .or_insert(CandleBar {
+                timestamp: bucket,
+                open: candle.open,
+                high: candle.high,
+                low: candle.low,
+                close: candle.close,
+            });
+    }
+
+    buckets.into_values().collect()
+}
+
+// Rows per INSERT, well below Postgres' bind parameter limit.
+const CANDLE_UPSERT_BATCH_SIZE: usize = 1000;
+
+// Writes bars fetched from an external feed as candles of `period`,
+// overwriting stored candles with the same timestamp.
+pub async fn upsert_candle_bars(
+    db: &DatabaseConnection,
+    chain_id: i64,
+    token: &str,
+    period: &str,
+    bars: &[CandleBar],
+    source: &str,
+) -> Result<(), DbErr> {
+    for batch in bars.chunks(CANDLE_UPSERT_BATCH_SIZE) {
+        let candles = batch.iter().map(|bar| price_candle::ActiveModel {
+            token: Set(token.to_string()),
+            open: Set(bar.open),
+            high: Set(bar.high),
+            low: Set(bar.low),
+            close: Set(bar.close),
+            timestamp: Set(bar.timestamp),
+            period: Set(period.to_string()),
+            chain_id: Set(chain_id),
+            aggregation_strategy: Set(None),
+            price_sources: Set(Some(source.to_string())),
+            spread: Set(None),
+            ..Default::default()
+        });
+
+        PriceCandle::insert_many(candles)
+            .on_conflict(
+                OnConflict::columns([
+                    price_candle::Column::ChainId,
+                    price_candle::Column::Token,
+                    price_candle::Column::Period,
+                    price_candle::Column::Timestamp,
+                ])
+                .update_columns([
+                    price_candle::Column::Open,
+                    price_candle::Column::High,
+                    price_candle::Column::Low,
+                    price_candle::Column::Close,
+                    price_candle::Column::PriceSources,
+                ])
+                .to_owned(),
+            )
+            .exec(db)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testdbutils::{clear_chain, flat_bar, insert_bars, test_db};
+
+    const TOKEN: &str = "0xtest";
+    const HOUR: i64 = 60 * 60;
+
+    fn timestamps(bars: &[CandleBar]) -> Vec<i64> {
+        bars.iter().map(|bar| bar.timestamp).collect()
+    }
+
+    async fn query(
+        db: &DatabaseConnection,
+        chain_id: i64,
+        period: &str,
+        range: &CandleRange,
+        order: CandleOrder,
+        limit: u64,
+    ) -> Vec<CandleBar> {
+        get_candles(db, chain_id, TOKEN, period, range, order, limit)
+            .await
+            .unwrap()
+    }
+
+    async fn newest(
+        db: &DatabaseConnection,
+        chain_id: i64,
+        period: &str,
+        range: &CandleRange,
+        limit: u64,
+    ) -> Vec<CandleBar> {
+        query(db, chain_id, period, range, CandleOrder::NewestFirst, limit).await
+    }
+
+    #[tokio::test]
+    async fn rejects_from_after_to() {
+        let db = DatabaseConnection::Disconnected;
+        let range = CandleRange { from: Some(200), to: Some(100), ..Default::default() };
+        let result = get_candles(&db, 1, TOKEN, "1h", &range, CandleOrder::NewestFirst, 10)
+            .await;
+        assert!(matches!(result, Err(ApiError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let desc = CandleOrder::NewestFirst;
+        let cursor = encode_cursor("BTC", "1h", desc, 1_760_000_400);
+        assert_eq!(decode_cursor(&cursor, "BTC", "1h", desc), Some(1_760_000_400));
+        assert_eq!(decode_cursor(&cursor, "ETH", "1h", desc), None);
+        assert_eq!(decode_cursor(&cursor, "BTC", "4h", desc), None);
+        assert_eq!(decode_cursor(&cursor, "BTC", "1h", CandleOrder::OldestFirst), None);
+        assert_eq!(decode_cursor("zz", "BTC", "1h", desc), None);
+    }
+
+    #[test]
+    fn aggregates_finer_candles_into_buckets() {
+        let candle = |timestamp: i64, open: &str, high: &str, low: &str, close: &str| {
+            price_candle::Model {
+                id: 0,
+                token: TOKEN.to_string(),
+                open: open.parse().unwrap(),
+                high: high.parse().unwrap(),
+                low: low.parse().unwrap(),
+                close: close.parse().unwrap(),
+                timestamp,
+                period: "1h".to_string(),
+                chain_id: 1,
+                aggregation_strategy: None,
+                price_sources: None,
+                spread: None,
+            }
+        };
+        let bars = aggregate_bars(
+            vec![
+                candle(0, "10", "12", "9", "11"),
+                candle(HOUR, "11", "15", "10", "14"),
+                candle(4 * HOUR, "14", "14", "13", "13"),
+            ],
+            4 * HOUR,
+        );
+
+        assert_eq!(timestamps(&bars), vec![0, 4 * HOUR]);
+        let first = &bars[0];
+        assert_eq!(first.open, "10".parse().unwrap());
+        assert_eq!(first.high, "15".parse().unwrap());
+        assert_eq!(first.low, "9".parse().unwrap());
+        assert_eq!(first.close, "14".parse().unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn stored_candles_in_range_newest_first() {
+        let db = test_db().await;
+        let chain_id = 914_001;
+        clear_chain(&db, chain_id).await;
+        let bars: Vec<CandleBar> = (0..10).map(|hour| flat_bar(hour * HOUR, "1")).collect();
+        insert_bars(&db, chain_id, TOKEN, "1h", &bars).await;
+
+        let all = newest(&db, chain_id, "1h", &CandleRange::default(), 100).await;
+        assert_eq!(timestamps(&all), (0..10).rev().map(|hour| hour * HOUR).collect::<Vec<_>>());
+
+        // from and to are inclusive.
+        let range = CandleRange { from: Some(2 * HOUR), to: Some(5 * HOUR), ..Default::default() };
+        let bounded = newest(&db, chain_id, "1h", &range, 100).await;
+        assert_eq!(timestamps(&bounded), vec![5 * HOUR, 4 * HOUR, 3 * HOUR, 2 * HOUR]);
+
+        // The newest candles come first and a cursor is exclusive.
+        let page = newest(&db, chain_id, "1h", &range, 2).await;
+        assert_eq!(timestamps(&page), vec![5 * HOUR, 4 * HOUR]);
+        let next = CandleRange { before: Some(4 * HOUR), ..range };
+        let page = newest(&db, chain_id, "1h", &next, 2).await;
+        assert_eq!(timestamps(&page), vec![3 * HOUR, 2 * HOUR]);
+
+        // Stored candles outside the range are not resampled.
+        let range = CandleRange { from: Some(20 * HOUR), to: None, ..Default::default() };
+        assert!(newest(&db, chain_id, "1h", &range, 100).await.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn resamples_from_the_coarsest_finer_period() {
+        let db = test_db().await;
+        let chain_id = 914_002;
+        clear_chain(&db, chain_id).await;
+        // 1h candles over two 4h buckets, plus 1m candles that must be ignored.
+        let hourly: Vec<CandleBar> = (0..8)
+            .map(|hour| flat_bar(hour * HOUR, &(hour + 1).to_string()))
+            .collect();
+        insert_bars(&db, chain_id, TOKEN, "1h", &hourly).await;
+        insert_bars(&db, chain_id, TOKEN, "1m", &[flat_bar(0, "100")]).await;
+
+        let bars = newest(&db, chain_id, "4h", &CandleRange::default(), 10).await;
+        assert_eq!(timestamps(&bars), vec![4 * HOUR, 0]);
+        let oldest = &bars[1];
+        assert_eq!((oldest.open, oldest.close), ("1".parse().unwrap(), "4".parse().unwrap()));
+        assert_eq!((oldest.low, oldest.high), ("1".parse().unwrap(), "4".parse().unwrap()));
+
+        // Bars are in range by their start and are built from the whole
+        // bucket.
+        let range = CandleRange { from: Some(3 * HOUR), to: Some(5 * HOUR), ..Default::default() };
+        let bars = newest(&db, chain_id, "4h", &range, 10).await;
+        assert_eq!(timestamps(&bars), vec![4 * HOUR]);
+        assert_eq!(bars[0].open, "5".parse().unwrap());
+        assert_eq!(bars[0].close, "8".parse().unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn resampled_pages_skip_gaps() {
+        let db = test_db().await;
+        let chain_id = 915_001;
+        clear_chain(&db, chain_id).await;
+        // 1h candles in 4h buckets 0, 1, 5 and 9; the buckets between are gaps.
+        let hourly: Vec<CandleBar> = [0, 1, 5, 9]
+            .iter()
+            .flat_map(|bucket| [bucket * 4 * HOUR, bucket * 4 * HOUR + HOUR])
+            .map(|timestamp| flat_bar(timestamp, "1"))
+            .collect();
+        insert_bars(&db, chain_id, TOKEN, "1h", &hourly).await;
+
+        // Pages stay full across the gaps and a cursor continues after them.
+        let page = newest(&db, chain_id, "4h", &CandleRange::default(), 2).await;
+        assert_eq!(timestamps(&page), vec![36 * HOUR, 20 * HOUR]);
+        let next = CandleRange { before: Some(20 * HOUR), ..Default::default() };
+        let page = newest(&db, chain_id, "4h", &next, 2).await;
+        assert_eq!(timestamps(&page), vec![4 * HOUR, 0]);
+        let next = CandleRange { before: Some(0), ..Default::default() };
+        assert!(newest(&db, chain_id, "4h", &next, 2).await.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn oldest_first_keeps_the_start_of_the_range() {
+        let db = test_db().await;
+        let chain_id = 916_001;
+        clear_chain(&db, chain_id).await;
+        let hourly: Vec<CandleBar> = (0..12).map(|hour| flat_bar(hour * HOUR, "1")).collect();
+        insert_bars(&db, chain_id, TOKEN, "1h", &hourly).await;
+
+        let range = CandleRange { from: Some(2 * HOUR), to: None, ..Default::default() };
+        let bars = query(&db, chain_id, "1h", &range, CandleOrder::OldestFirst, 3).await;
+        assert_eq!(timestamps(&bars), vec![2 * HOUR, 3 * HOUR, 4 * HOUR]);
+
+        // A forward cursor is exclusive and keeps `to`.
+        let range = CandleRange { to: Some(6 * HOUR), ..range };
+        let next = CandleOrder::OldestFirst.page_after(range, 4 * HOUR);
+        let bars = query(&db, chain_id, "1h", &next, CandleOrder::OldestFirst, 3).await;
+        assert_eq!(timestamps(&bars), vec![5 * HOUR, 6 * HOUR]);
+
+        let range = CandleRange { from: Some(4 * HOUR), to: None, ..Default::default() };
+        let bars = query(&db, chain_id, "4h", &range, CandleOrder::OldestFirst, 1).await;
+        assert_eq!(timestamps(&bars), vec![4 * HOUR]);
+        let bars = query(&db, chain_id, "4h", &CandleRange::default(), CandleOrder::OldestFirst, 5)
+            .await;
+        assert_eq!(timestamps(&bars), vec![0, 4 * HOUR, 8 * HOUR]);
+        let next = CandleOrder::OldestFirst.page_after(CandleRange::default(), 0);
+        let bars = query(&db, chain_id, "4h", &next, CandleOrder::OldestFirst, 1).await;
+        assert_eq!(timestamps(&bars), vec![4 * HOUR]);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::utils::aggregationutils::AggregatedPrice;
+use crate::utils::candlesutils::CandleBar;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+// Events buffered per subscriber; slower clients skip what they missed.
+pub const PRICE_EVENT_CHANNEL_SIZE: usize = 1024;
+
+// Live updates produced by the price submitters, pushed to API clients.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceEvent {
+    Price {
+        chain_id: i64,
+        token_symbol: String,
+        token_address: String,
+        price: Decimal,
+        aggregation_strategy: &'static str,
+        price_sources: String,
+        timestamp: u64,
+    },
+    Candle {
+        chain_id: i64,
+        token_symbol: String,
+        token_address: String,
+        period: &'static str,
+        candle: CandleBar,
+    },
+}
+
+impl PriceEvent {
+    pub fn price(
+        chain_id: i64,
+        token_symbol: &str,
+        token_address: &str,
+        aggregated: &AggregatedPrice,
+        timestamp: u64,
+    ) -> Option<Self> {
+        Some(PriceEvent::Price {
+            chain_id,
+            token_symbol: token_symbol.to_string(),
+            token_address: token_address.to_string(),
+            price: aggregated.decimal_price()?,
+            aggregation_strategy: aggregated.strategy.as_str(),
+            price_sources: aggregated.sources_label(),
+            timestamp,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PriceEvent::Price { .. } => "price",
+            PriceEvent::Candle { .. } => "candle",
+        }
+    }
+
+    pub fn chain_id(&self) -> i64 {
+        match self {
+            PriceEvent::Price { chain_id, .. } | PriceEvent::Candle { chain_id, .. } => *chain_id,
+        }
+    }
+
+    pub fn token_symbol(&self) -> &str {
+        match self {
+            PriceEvent::Price { token_symbol, .. } | PriceEvent::Candle { token_symbol, .. } => {
+                token_symbol
+            }
+        }
+    }
+
+    pub fn period(&self) -> Option<&'static str> {
+        match self {
+            PriceEvent::Price { .. } => None,
+            PriceEvent::Candle { period, .. } => Some(period),
+        }
+    }
+}
+
+// Shared between the submitters and the Rocket server (as managed state).
+#[derive(Clone)]
+pub struct PriceEvents(pub broadcast::Sender<PriceEvent>);
+
+impl PriceEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(PRICE_EVENT_CHANNEL_SIZE);
+        PriceEvents(sender)
+    }
+
+    // Sending only fails when nobody is subscribed, which is fine.
+    pub fn publish(&self, event: PriceEvent) {
+        let _ = self.0.send(event);
+    }
+}
+
+impl Default for PriceEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,484 @@
+use crate::configs::envconfig::ENV;
+use crate::utils::helpersutils::{
+    BINANCE_KEYS,
+    BINANCE_SYMBOL_MAP,
+    BYBIT_KEYS,
+    BYBIT_SYMBOL_MAP,
+    GATE_KEYS,
+    GATE_SYMBOL_MAP,
+    KRAKEN_KEYS,
+    KRAKEN_SYMBOL_MAP,
+    KUCOIN_KEYS,
+    KUCOIN_SYMBOL_MAP,
+    MEXC_KEYS,
+    MEXC_SYMBOL_MAP,
+    OKX_KEYS,
+    OKX_SYMBOL_MAP,
+};
+use crate::utils::responseinterfaceutils::{
+    BinanceTicker, BybitResponse, BybitStatus, GateTicker, KrakenResponse, KucoinResponse,
+    KucoinStatus, OkxResponse, OkxStatus,
+};
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const EXCHANGE_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+pub type ExchangeKeys = Lazy<HashMap<i64, Vec<&'static str>>>;
+pub type ExchangeSymbolMap = Lazy<HashMap<i64, HashMap<&'static str, &'static str>>>;
+
+// A centralized exchange whose public ticker endpoint returns the last price
+// for every listed market in one response.
+pub trait PriceSource: Sync {
+    fn name(&self) -> &'static str;
+
+    fn default_base_url(&self) -> &'static str;
+
+    // Path and query of the ticker endpoint, appended to the base URL.
+    fn ticker_path(&self) -> &'static str;
+
+    fn keys(&self) -> &'static ExchangeKeys;
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap;
+
+    // Parses the ticker response body into exchange ticker -> last price.
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>>;
+
+    // `EXCHANGE_BASE_URLS` in env.json overrides the public endpoint, e.g. to
+    // point an adapter at a local mock server.
+    fn base_url(&self) -> String {
+        match ENV.EXCHANGE_BASE_URLS.get(self.name()) {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => self.default_base_url().to_string(),
+        }
+    }
+}
+
+fn parse_price(price: &str) -> Option<f64> {
+    price.parse::<f64>().ok().filter(|price| *price > 0.0)
+}
+
+pub struct Binance;
+
+impl PriceSource for Binance {
+    fn name(&self) -> &'static str {
+        "binance"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.binance.com"
+    }
+
+    fn ticker_path(&self) -> &'static str {
+        "/api/v3/ticker/price"
+    }
+
+    fn keys(&self) -> &'static ExchangeKeys {
+        &BINANCE_KEYS
+    }
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap {
+        &BINANCE_SYMBOL_MAP
+    }
+
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let tickers: Vec<BinanceTicker> = serde_json::from_str(body)?;
+        Ok(tickers
+            .into_iter()
+            .filter_map(|ticker| Some((ticker.symbol, parse_price(&ticker.price)?)))
+            .collect())
+    }
+}
+
+pub struct Kucoin;
+
+impl PriceSource for Kucoin {
+    fn name(&self) -> &'static str {
+        "kucoin"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.kucoin.com"
+    }
+
+    fn ticker_path(&self) -> &'static str {
+        "/api/v1/market/allTickers"
+    }
+
+    fn keys(&self) -> &'static ExchangeKeys {
+        &KUCOIN_KEYS
+    }
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap {
+        &KUCOIN_SYMBOL_MAP
+    }
+
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let status: KucoinStatus = serde_json::from_str(body)?;
+        if status.code != "200000" {
+            return Err(format!("KuCoin returned {}: {}", status.code, status.msg).into());
+        }
+        let response: KucoinResponse = serde_json::from_str(body)?;
+        Ok(response
+            .data
+            .ticker
+            .into_iter()
+            .filter_map(|ticker| Some((ticker.symbol, parse_price(ticker.last.as_deref()?)?)))
+            .collect())
+    }
+}
+
+pub struct Mexc;
+
+impl PriceSource for Mexc {
+    fn name(&self) -> &'static str {
+        "mexc"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.mexc.com"
+    }
+
+    fn ticker_path(&self) -> &'static str {
+        "/api/v3/ticker/price"
+    }
+
+    fn keys(&self) -> &'static ExchangeKeys {
+        &MEXC_KEYS
+    }
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap {
+        &MEXC_SYMBOL_MAP
+    }
+
+    // MEXC mirrors the Binance spot ticker schema.
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        Binance.parse_tickers(body)
+    }
+}
+
+pub struct Gate;
+
+impl PriceSource for Gate {
+    fn name(&self) -> &'static str {
+        "gate"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.gateio.ws"
+    }
+
+    fn ticker_path(&self) -> &'static str {
+        "/api/v4/spot/tickers"
+    }
+
+    fn keys(&self) -> &'static ExchangeKeys {
+        &GATE_KEYS
+    }
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap {
+        &GATE_SYMBOL_MAP
+    }
+
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let tickers: Vec<GateTicker> = serde_json::from_str(body)?;
+        Ok(tickers
+            .into_iter()
+            .filter_map(|ticker| Some((ticker.currency_pair, parse_price(&ticker.last)?)))
+            .collect())
+    }
+}
+
+pub struct Bybit;
+
+impl PriceSource for Bybit {
+    fn name(&self) -> &'static str {
+        "bybit"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.bybit.com"
+    }
+
+    fn ticker_path(&self) -> &'static str {
+        "/v5/market/tickers?category=spot"
+    }
+
+    fn keys(&self) -> &'static ExchangeKeys {
+        &BYBIT_KEYS
+    }
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap {
+        &BYBIT_SYMBOL_MAP
+    }
+
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let status: BybitStatus = serde_json::from_str(body)?;
+        if status.ret_code != 0 {
+            return Err(format!("Bybit returned {}: {}", status.ret_code, status.ret_msg).into());
+        }
+        let response: BybitResponse = serde_json::from_str(body)?;
+        Ok(response
+            .result
+            .list
+            .into_iter()
+            .filter_map(|ticker| Some((ticker.symbol, parse_price(&ticker.last_price)?)))
+            .collect())
+    }
+}
+
+pub struct Okx;
+
+impl PriceSource for Okx {
+    fn name(&self) -> &'static str {
+        "okx"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://www.okx.com"
+    }
+
+    fn ticker_path(&self) -> &'static str {
+        "/api/v5/market/tickers?instType=SPOT"
+    }
+
+    fn keys(&self) -> &'static ExchangeKeys {
+        &OKX_KEYS
+    }
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap {
+        &OKX_SYMBOL_MAP
+    }
+
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let status: OkxStatus = serde_json::from_str(body)?;
+        if status.code != "0" {
+            return Err(format!("OKX returned {}: {}", status.code, status.msg).into());
+        }
+        let response: OkxResponse = serde_json::from_str(body)?;
+        Ok(response
+            .data
+            .into_iter()
+            .filter_map(|ticker| Some((ticker.inst_id, parse_price(&ticker.last)?)))
+            .collect())
+    }
+}
+
+pub struct Kraken;
+
+impl PriceSource for Kraken {
+    fn name(&self) -> &'static str {
+        "kraken"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.kraken.com"
+    }
+
+    fn ticker_path(&self) -> &'static str {
+        "/0/public/Ticker"
+    }
+
+    fn keys(&self) -> &'static ExchangeKeys {
+        &KRAKEN_KEYS
+    }
+
+    fn symbol_map(&self) -> &'static ExchangeSymbolMap {
+        &KRAKEN_SYMBOL_MAP
+    }
+
+    fn parse_tickers(&self, body: &str) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let response: KrakenResponse = serde_json::from_str(body)?;
+        if !response.error.is_empty() {
+            return Err(format!("Kraken returned {:?}", response.error).into());
+        }
+        Ok(response
+            .result
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(pair, ticker)| Some((pair, parse_price(ticker.c.first()?)?)))
+            .collect())
+    }
+}
+
+pub static PRICE_SOURCES: [&dyn PriceSource; 7] =
+    [&Binance, &Kucoin, &Mexc, &Gate, &Bybit, &Okx, &Kraken];
+
+// Fetches the ticker endpoint of `source` under `base_url` and returns
+// exchange ticker -> last price.
+async fn fetch_tickers(
+    client: &reqwest::Client,
+    source: &dyn PriceSource,
+    base_url: &str,
+) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+    let url = format!("{}{}", base_url, source.ticker_path());
+    let response = client.get(url).send().await?;
+
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(format!(
+            "Failed to retrieve {} tickers. Status code: {}",
+            source.name(),
+            response.status()
+        )
+        .into());
+    }
+
+    let body = response.text().await?;
+    source.parse_tickers(&body)
+}
+
+// Fetches one exchange and returns asset symbol -> last price for the
+// tickers configured on `chain_id`.
+pub async fn fetch_source_prices(
+    client: &reqwest::Client,
+    source: &dyn PriceSource,
+    chain_id: i64,
+) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+    let (keys, symbol_map) = match (source.keys().get(&chain_id), source.symbol_map().get(&chain_id)) {
+        (Some(keys), Some(symbol_map)) if !keys.is_empty() => (keys, symbol_map),
+        _ => return Ok(HashMap::new()),
+    };
+
+    let tickers = fetch_tickers(client, source, &source.base_url()).await?;
+
+    Ok(keys
+        .iter()
+        .filter_map(|key| {
+            let price = tickers.get(*key)?;
+            let symbol = symbol_map.get(key)?;
+            Some((symbol.to_string(), *price))
+        })
+        .collect())
+}
+
+// Queries every exchange concurrently. A failing exchange is logged and left
+// out so the remaining sources still contribute.
+pub async fn get_exchange_prices(chain_id: i64) -> Vec<(&'static str, HashMap<String, f64>)> {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(EXCHANGE_REQUEST_TIMEOUT_SECS))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Error building exchange client: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let requests = PRICE_SOURCES.iter().map(|source| {
+        let client = &client;
+        async move {
+            match fetch_source_prices(client, *source, chain_id).await {
+                Ok(prices) => Some((source.name(), prices)),
+                Err(e) => {
+                    eprintln!("Error getting {} prices: {}", source.name(), e);
+                    None
+                }
+            }
+        }
+    });
+
+    join_all(requests).await.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // Serves `response` on the ticker endpoint of `source` and fetches it.
+    async fn fetch_from_mock(
+        source: &dyn PriceSource,
+        response: ResponseTemplate,
+    ) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        let ticker_path = source.ticker_path().split('?').next().unwrap_or_default();
+        Mock::given(method("GET"))
+            .and(path(ticker_path))
+            .respond_with(response)
+            .expect(1)
+            .mount(&server)
+            .await;
+        fetch_tickers(&reqwest::Client::new(), source, &server.uri()).await
+    }
+
+    async fn fetch_fixture(
+        source: &dyn PriceSource,
+        fixture: &str,
+    ) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
+        let body = std::fs::read_to_string(format!("tests/fixtures/exchanges/{}.json", fixture))
+            .expect("Failed to read fixture");
+        fetch_from_mock(source, ResponseTemplate::new(200).set_body_string(body)).await
+    }
+
+    fn assert_prices(prices: &HashMap<String, f64>, expected: &[(&str, f64)]) {
+        let expected: HashMap<String, f64> = expected
+            .iter()
+            .map(|(ticker, price)| (ticker.to_string(), *price))
+            .collect();
+        assert_eq!(*prices, expected);
+    }
+
+    #[tokio::test]
+    async fn binance_tickers() {
+        let prices = fetch_fixture(&Binance, "binance").await.unwrap();
+        assert_prices(&prices, &[("BTCUSDT", 67012.34), ("ETHUSDT", 2634.18)]);
+        assert!(fetch_fixture(&Binance, "binance_error").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn kucoin_tickers() {
+        let prices = fetch_fixture(&Kucoin, "kucoin").await.unwrap();
+        assert_prices(&prices, &[("BTC-USDT", 67012.1), ("ETH-USDT", 2634.15)]);
+        let error = fetch_fixture(&Kucoin, "kucoin_error").await.unwrap_err();
+        assert!(error.to_string().contains("429000"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn mexc_tickers() {
+        let prices = fetch_fixture(&Mexc, "mexc").await.unwrap();
+        assert_prices(&prices, &[("BTCUSDT", 67013.02), ("ETHUSDT", 2634.21)]);
+        assert!(fetch_fixture(&Mexc, "binance_error").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn gate_tickers() {
+        let prices = fetch_fixture(&Gate, "gate").await.unwrap();
+        assert_prices(&prices, &[("BTC_USDT", 67010.5), ("ETH_USDT", 2634.02)]);
+        assert!(fetch_fixture(&Gate, "gate_error").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn bybit_tickers() {
+        let prices = fetch_fixture(&Bybit, "bybit").await.unwrap();
+        assert_prices(&prices, &[("BTCUSDT", 67011.85), ("ETHUSDT", 2634.1)]);
+        let error = fetch_fixture(&Bybit, "bybit_error").await.unwrap_err();
+        assert!(error.to_string().contains("10006"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn okx_tickers() {
+        let prices = fetch_fixture(&Okx, "okx").await.unwrap();
+        assert_prices(&prices, &[("BTC-USDT", 67012.7), ("ETH-USDT", 2634.3)]);
+        let error = fetch_fixture(&Okx, "okx_error").await.unwrap_err();
+        assert!(error.to_string().contains("50011"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn kraken_tickers() {
+        let prices = fetch_fixture(&Kraken, "kraken").await.unwrap();
+        assert_prices(&prices, &[("XXBTZUSD", 67015.1), ("XETHZUSD", 2634.4)]);
+        let error = fetch_fixture(&Kraken, "kraken_error").await.unwrap_err();
+        assert!(error.to_string().contains("Too many requests"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn http_error_status() {
+        let response = ResponseTemplate::new(503).set_body_string("Service Unavailable");
+        let error = fetch_from_mock(&Binance, response).await.unwrap_err();
+        assert!(error.to_string().contains("503"), "{}", error);
+    }
+}
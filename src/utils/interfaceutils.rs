@@ -1,4 +1,5 @@
 use chrono::Utc;
+use rust_decimal::Decimal;
 use serde::Serialize;
 
 // #[derive(Clone)]
@@ -23,7 +24,18 @@ pub struct AssetPricingInfo2 {
     pub min_price: Option<String>,
     pub max_price: Option<String>,
     pub updated_at: chrono::DateTime<Utc>,
-    pub price_decimals : f32
+    // Seconds since updated_at
+    pub age_seconds: u64,
+    // age_seconds is over the asset class's staleness budget
+    pub stale: bool,
+    // Whether the asset's trading calendar has a session open right now
+    pub is_market_open: bool,
+    // updated_at, or the end of the last session before it if the price was
+    // taken while the market was closed
+    pub last_trade_at: Option<chrono::DateTime<Utc>>,
+    pub price_decimals : f32,
+    // Spread policy behind min_price / max_price.
+    pub spread_policy: String
 }
 
 #[derive(Debug, Serialize)]
@@ -34,10 +46,13 @@ pub struct PriceCandleResponse {
 #[derive(Debug, Serialize)]
 pub struct Price24HResponse {
     pub _id : String,
-    pub high: f32,
-    pub low: f32,
-    pub open: f32,
-    pub close: f32
+    pub high: Decimal,
+    pub low: Decimal,
+    pub open: Decimal,
+    pub close: Decimal,
+    // close - open over the window
+    pub change: Decimal,
+    pub change_percent: Decimal
 }
 
 #[derive(Debug, Serialize)]
@@ -0,0 +1,322 @@
+use std::collections::{BTreeMap, HashMap};
+
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::configs::envconfig::{ENV, SERVED_CHAIN_IDS};
+use crate::configs::registryconfig::{AssetClass, REGISTRY};
+use crate::utils::calendarutils::{TradingSchedule, TRADING_CALENDAR};
+use crate::utils::candlesutils::{upsert_candle_bars, CandleBar};
+use crate::utils::helpersutils::{sleep_ms, PERIOD_MAP, SYMBOL_TO_ADDRESS_MAPPING};
+use crate::utils::latestpricesutils::{LatestPrice, LatestPriceCache};
+
+const INVESTING_PRICE_SOURCE: &str = "investing";
+
+// History requested per candle period: (range fetched once at startup, range
+// fetched on every refresh). Investing only serves short intraday histories.
+pub static INVESTING_PERIOD_RANGES: Lazy<HashMap<&'static str, (&'static str, &'static str)>> =
+    Lazy::new(|| {
+        let mut map = HashMap::new();
+        map.insert("1m", ("P1D", "P1D"));
+        map.insert("5m", ("P1W", "P1D"));
+        map.insert("15m", ("P1M", "P1D"));
+        map.insert("1h", ("P3M", "P1W"));
+        map.insert("1d", ("P5Y", "P1M"));
+        map
+    });
+
+// One group of instruments pulled from investing charts. The fetch interval
+// comes from the asset class's config and the trading hours from each
+// asset's trading calendar schedule.
+pub struct InvestingFeed {
+    pub asset_class: AssetClass,
+    // Registry symbol -> investing instrument id.
+    pub symbol_ids: &'static HashMap<&'static str, &'static str>,
+    // Candle period -> investing chart interval.
+    pub intervals: &'static HashMap<&'static str, &'static str>,
+}
+
+#[derive(Deserialize)]
+struct InvestingChartResponse {
+    data: Vec<Vec<Value>>,
+}
+
+pub fn get_investing_chart_url(investing_id: &str, interval: &str, range: &str) -> String {
+    format!(
+        "{}/api/financialdata/{}/historical/chart/?interval={}&period={}&pointscount=160",
+        ENV.INVESTING_BASE_URL, investing_id, interval, range
+    )
+}
+
+fn parse_decimal(value: &Value) -> Option<Decimal> {
+    let number = value.as_number()?.to_string();
+    number
+        .parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(&number))
+        .ok()
+        .map(|decimal| decimal.normalize())
+}
+
+// Parses an investing chart body (`{"data": [[ts_ms, open, high, low, close,
+// volume, ...], ...]}`) into bars of `period`, oldest first. Malformed rows
+// and rows with a price that is not positive are skipped. So are bars from a
+// closed market: those whose bucket starts outside `schedule`'s sessions or
+// on one of its holidays, and flat bars without volume. When several rows
+// fall in the same bucket, the last one is kept.
+pub fn parse_investing_chart(
+    body: &str,
+    period: &str,
+    schedule: &TradingSchedule,
+) -> Result<Vec<CandleBar>, Box<dyn std::error::Error>> {
+    let interval = *PERIOD_MAP
+        .get(period)
+        .ok_or_else(|| format!("Unsupported period {}", period))? as i64;
+    let response: InvestingChartResponse = serde_json::from_str(body)?;
+
+    let mut bars: BTreeMap<i64, CandleBar> = BTreeMap::new();
+    for row in &response.data {
+        let (Some(timestamp_ms), Some(open), Some(high), Some(low), Some(close)) = (
+            row.first().and_then(Value::as_i64),
+            row.get(1).and_then(parse_decimal),
+            row.get(2).and_then(parse_decimal),
+            row.get(3).and_then(parse_decimal),
+            row.get(4).and_then(parse_decimal),
+        ) else {
+            continue;
+        };
+        if [open, high, low, close].iter().any(|value| *value <= Decimal::ZERO) {
+            continue;
+        }
+
+        let timestamp = (timestamp_ms / 1000).div_euclid(interval) * interval;
+        let volume = row.get(5).and_then(parse_decimal).unwrap_or_default();
+        let flat = open == high && high == low && low == close;
+        if !schedule.is_open(timestamp) || (flat && volume.is_zero()) {
+            continue;
+        }
+
+        bars.insert(
+            timestamp,
+            CandleBar {
+                timestamp,
+                open,
+                high: high.max(open).max(close),
+                low: low.min(open).min(close),
+                close,
+            },
+        );
+    }
+
+    Ok(bars.into_values().collect())
+}
+
+async fn fetch_investing_chart(
+    client: &reqwest::Client,
+    investing_id: &str,
+    interval: &str,
+    period: &str,
+    range: &str,
+    schedule: &TradingSchedule,
+) -> Result<Vec<CandleBar>, Box<dyn std::error::Error>> {
+    let response = client
+        .get(get_investing_chart_url(investing_id, interval, range))
+        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36")
+        .header("domain-id", "www")
+        .send()
+        .await?;
+
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(format!(
+            "Failed to retrieve investing chart {}. Status code: {}",
+            investing_id,
+            response.status()
+        )
+        .into());
+    }
+
+    let body = response.text().await?;
+    parse_investing_chart(&body, period, schedule)
+}
+
+// Fetches every instrument and period of the feed once and writes the bars to
+// each served chain the symbol is listed on. `backfill` requests the longer
+// history.
+async fn sync_investing_candles(
+    db: &DatabaseConnection,
+    client: &reqwest::Client,
+    latest_prices: &LatestPriceCache,
+    feed: &InvestingFeed,
+    backfill: bool,
+) {
+    let name = feed.asset_class.as_str();
+    for (symbol, investing_id) in feed.symbol_ids.iter() {
+        let listings: Vec<(i64, &String)> = SERVED_CHAIN_IDS
+            .iter()
+            .filter_map(|chain_id| {
+                let address = SYMBOL_TO_ADDRESS_MAPPING.get(chain_id)?.get(*symbol)?;
+                Some((*chain_id, address))
+            })
+            .collect();
+        let Some(asset) = listings
+            .first()
+            .and_then(|(chain_id, _)| REGISTRY.asset(*chain_id, symbol))
+        else {
+            continue;
+        };
+        let schedule = TRADING_CALENDAR.for_asset(asset);
+
+        // Newest close and its bar time across periods, for the tickers.
+        let mut latest: HashMap<i64, (i64, Decimal)> = HashMap::new();
+
+        for (period, (backfill_range, refresh_range)) in INVESTING_PERIOD_RANGES.iter() {
+            let Some(interval) = feed.intervals.get(period) else {
+                continue;
+            };
+            let range = if backfill { backfill_range } else { refresh_range };
+            let bars = match fetch_investing_chart(
+                client,
+                investing_id,
+                interval,
+                period,
+                range,
+                schedule,
+            )
+            .await
+            {
+                Ok(bars) => bars,
+                Err(e) => {
+                    eprintln!("Error fetching {} {} {} bars: {}", name, symbol, period, e);
+                    continue;
+                }
+            };
+            let Some(last_bar) = bars.last() else {
+                continue;
+            };
+
+            for (chain_id, address) in &listings {
+                if let Err(e) = upsert_candle_bars(
+                    db,
+                    *chain_id,
+                    address,
+                    period,
+                    &bars,
+                    INVESTING_PRICE_SOURCE,
+                )
+                .await
+                {
+                    eprintln!("Error storing {} {} {} bars: {}", name, symbol, period, e);
+                    continue;
+                }
+                let entry = latest.entry(*chain_id).or_insert((i64::MIN, last_bar.close));
+                if last_bar.timestamp >= entry.0 {
+                    *entry = (last_bar.timestamp, last_bar.close);
+                }
+            }
+        }
+
+        for (chain_id, (timestamp, close)) in latest {
+            latest_prices.update(
+                chain_id,
+                symbol,
+                LatestPrice {
+                    price: close,
+                    spread: None,
+                    updated_at: timestamp.max(0) as u64,
+                },
+            );
+        }
+    }
+}
+
+// Backfills the feed once, then refreshes the recent bars on the asset
+// class's update interval.
+pub async fn run_investing_feed(
+    db: &DatabaseConnection,
+    latest_prices: LatestPriceCache,
+    feed: &InvestingFeed,
+) {
+    let client = reqwest::Client::new();
+    let mut backfill = true;
+    loop {
+        sync_investing_candles(db, &client, &latest_prices, feed, backfill).await;
+        backfill = false;
+        sleep_ms(REGISTRY.class_config(feed.asset_class).update_interval_ms).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Commodities trade on the weekdays schedule (Sunday 22:00 to Friday
+    // 22:00 UTC).
+    fn weekdays_schedule() -> &'static TradingSchedule {
+        let chain_id = REGISTRY.networks["bitlayer_testnet"].chain_id;
+        TRADING_CALENDAR.for_asset(REGISTRY.asset(chain_id, "XAU").expect("XAU not listed"))
+    }
+
+    fn decimal(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_open_session_bars() {
+        let body = std::fs::read_to_string("tests/fixtures/investing/chart_1h.json")
+            .expect("Failed to read fixture");
+        let bars = parse_investing_chart(&body, "1h", weekdays_schedule()).unwrap();
+
+        let timestamps: Vec<i64> = bars.iter().map(|bar| bar.timestamp).collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                // Friday 20:00 and 21:00; Friday 22:00 to Sunday 22:00 is
+                // closed
+                1792180800, 1792184400,
+                // Sunday 22:00; the flat bar without volume at 23:00 is
+                // dropped, the flat one with volume at 00:00 is kept
+                1792360800, 1792368000,
+                // Monday 03:00; the malformed and negative rows before it are
+                // skipped
+                1792378800,
+            ]
+        );
+
+        let first = &bars[0];
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (decimal("4.012"), decimal("4.031"), decimal("4.005"), decimal("4.027"))
+        );
+        // The last bar of a bucket wins.
+        let last = &bars[4];
+        assert_eq!((last.high, last.close), (decimal("4.072"), decimal("4.07")));
+    }
+
+    #[test]
+    fn skips_holidays_and_widens_inconsistent_bars() {
+        // Christmas 2026 is a holiday, 2026-12-24 12:00 is not.
+        let body = r#"{"data": [
+            [1798113600000, 1.0, 1.1, 0.9, 1.05, 10],
+            [1798200000000, 2.0, 1.8, 1.9, 1.85, 10]
+        ]}"#;
+        let bars = parse_investing_chart(body, "1h", weekdays_schedule()).unwrap();
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].timestamp, 1798113600);
+
+        // High and low are widened to include open and close.
+        let body = r#"{"data": [[1798113600000, 2.0, 1.8, 1.9, 1.85, 10]]}"#;
+        let bars = parse_investing_chart(body, "1h", weekdays_schedule()).unwrap();
+        assert_eq!((bars[0].high, bars[0].low), (decimal("2"), decimal("1.85")));
+    }
+
+    #[test]
+    fn rejects_bad_bodies_and_periods() {
+        assert!(parse_investing_chart("<html>", "1h", weekdays_schedule()).is_err());
+        assert!(parse_investing_chart(r#"{"data": []}"#, "2h", weekdays_schedule()).is_err());
+        assert!(parse_investing_chart(r#"{"data": []}"#, "1h", weekdays_schedule())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy)]
+pub struct LatestPrice {
+    pub price: Decimal,
+    // Half-width of the ticker band, if the price came with one.
+    pub spread: Option<Decimal>,
+    // Unix seconds the price was produced. Prices loaded from the database
+    // carry their candle's start time instead.
+    pub updated_at: u64,
+}
+
+impl LatestPrice {
+    pub fn age_seconds(&self, now: u64) -> u64 {
+        now.saturating_sub(self.updated_at)
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Default)]
+struct ChainPrices {
+    prices: HashMap<String, LatestPrice>,
+    // Loaded from the database once; tokens it had no price for stay missing
+    // until a submitter or feed writes one.
+    seeded: bool,
+}
+
+// Newest price per chain and token symbol. The submitters write to it and the
+// ticker endpoint reads from it (as managed state).
+#[derive(Clone, Default)]
+pub struct LatestPriceCache(Arc<RwLock<HashMap<i64, ChainPrices>>>);
+
+impl LatestPriceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&self, chain_id: i64, token_symbol: &str, latest: LatestPrice) {
+        let mut chains = self.0.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        chains
+            .entry(chain_id)
+            .or_default()
+            .prices
+            .insert(token_symbol.to_string(), latest);
+    }
+
+    // Fills in tokens the cache has no price for yet, e.g. after a restart,
+    // and marks the chain as seeded.
+    pub fn seed(&self, chain_id: i64, prices: &HashMap<String, LatestPrice>) {
+        let mut chains = self.0.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let chain_prices = chains.entry(chain_id).or_default();
+        for (token_symbol, latest) in prices {
+            chain_prices.prices.entry(token_symbol.clone()).or_insert(*latest);
+        }
+        chain_prices.seeded = true;
+    }
+
+    pub fn is_seeded(&self, chain_id: i64) -> bool {
+        let chains = self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        chains.get(&chain_id).is_some_and(|chain_prices| chain_prices.seeded)
+    }
+
+    pub fn chain_prices(&self, chain_id: i64) -> HashMap<String, LatestPrice> {
+        let chains = self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        chains
+            .get(&chain_id)
+            .map(|chain_prices| chain_prices.prices.clone())
+            .unwrap_or_default()
+    }
+}
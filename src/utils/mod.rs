@@ -3,3 +3,16 @@ pub mod helpersutils;
 pub mod pricesutils;
 pub mod responseinterfaceutils;
 pub mod interfaceutils;
+pub mod exchangesutils;
+pub mod aggregationutils;
+pub mod pythstreamutils;
+pub mod pythvalidationutils;
+pub mod candlesutils;
+pub mod statsutils;
+pub mod eventsutils;
+pub mod latestpricesutils;
+pub mod apierrorutils;
+pub mod investingutils;
+pub mod calendarutils;
+#[cfg(test)]
+pub mod testdbutils;
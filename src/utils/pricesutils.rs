@@ -1,51 +1,39 @@
+use crate::configs::envconfig::ENV;
+use crate::configs::registryconfig::{AssetRecord, DerivedPrice, SpreadPolicy, REGISTRY};
+use crate::utils::aggregationutils::{aggregate_prices, AggregatedPrice, PriceSample};
+use crate::utils::pythvalidationutils::validate_pyth_price;
 use crate::utils::helpersutils::{
-    // BINANCE_KEYS,
-    // BINANCE_SYMBOL_MAP,
-    // KUCOIN_KEYS,
-    // KUCOIN_SYMBOL_MAP,
-    // MEXC_KEYS,
-    // MEXC_SYMBOL_MAP,
-    // GATE_KEYS,
-    // GATE_SYMBOL_MAP,
     PRICES_MAPPINGS,
     PYTH_ID,
-    // OKX_KEYS,
-    // OKX_SYMBOL_MAP,
-    // KRAKEN_KEYS,
-    // KRAKEN_SYMBOL_MAP,
-    // BYBIT_KEYS,
-    // BYBIT_SYMBOL_MAP,
     PYTH_ID_TO_TOKEN_MAPPING,
-    SUPPORTED_TOKENS,
     SYMBOL_TO_ADDRESS_MAPPING,
     SYMBOL_TO_DECIMAL_MAPPING,
 };
 
-#[path = "../../entity/src/mod.rs"]
-mod entities;
-
-use crate::configs::envconfig::{CHAINID_MAP, ENV};
 
 use crate::utils::responseinterfaceutils::{
-    ParclDetails, ParclIdResponse, ParclResponse, PythResponse,
+    Info, ParclIdResponse, ParclResponse, PythResponse,
 };
-use chrono::Utc;
-use entities::{prelude::*, *};
+use chrono::{TimeZone, Utc};
+use crate::entities::prelude::*;
 use sea_orm::entity::prelude::*;
-use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use sea_orm::{DatabaseConnection, DbBackend, DbErr, EntityTrait, Statement};
 use std::collections::HashMap;
-extern crate rand;
 use num_bigint::BigInt;
-use rand::Rng;
+use rust_decimal::RoundingStrategy;
 
+use super::apierrorutils::ApiError;
+use super::calendarutils::TRADING_CALENDAR;
 use super::interfaceutils::AssetPricingInfo2;
+use super::latestpricesutils::{unix_now, LatestPrice, LatestPriceCache};
 
 const PRICE_DECIMALS: usize = 4;
-const PRECISION: i32 = 10;
+// Decimal places a ticker price is rounded to before scaling.
+const PRECISION: u32 = 10;
 
-pub fn get_pyth_price_url() -> String {
-    let mut pyth_url = String::from("https://hermes.pyth.network/v2/updates/price/latest?");
-    let pyth_ids = match PYTH_ID.get(&ENV.NETWORK) {
+pub fn get_pyth_price_url(chain_id: i64) -> String {
+    let mut pyth_url = format!("{}/v2/updates/price/latest?", ENV.HERMES_BASE_URL);
+    let pyth_ids = match PYTH_ID.get(&chain_id) {
         Some(ids) => ids,
         None => return pyth_url,
     };
@@ -54,22 +42,45 @@ pub fn get_pyth_price_url() -> String {
         if i > 0 {
             pyth_url.push('&');
         }
-        pyth_url.push_str(&format!("ids[]={}", id.to_string()));
+        pyth_url.push_str(&format!("ids[]={}", id));
     }
 
     pyth_url
 }
 
-pub async fn get_pyth_prices() -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
-    let mut result: HashMap<String, f64> = HashMap::new();
+// Maps a Hermes price update (latest or streamed) to token -> Pyth sample.
+pub fn parse_pyth_response(chain_id: i64, response_data: &PythResponse) -> HashMap<String, PriceSample> {
+    let mut result: HashMap<String, PriceSample> = HashMap::new();
 
-    let pyth_id_to_token_mapping = match PYTH_ID_TO_TOKEN_MAPPING.get(&ENV.NETWORK) {
+    let pyth_id_to_token_mapping = match PYTH_ID_TO_TOKEN_MAPPING.get(&chain_id) {
         Some(ids) => ids,
-        None => panic!("No mapping found for the given network"),
+        None => return result,
     };
 
+    let now = Utc::now().timestamp().max(0) as u64;
+
+    for price_data in response_data.parsed.iter() {
+        if let Some(token) = pyth_id_to_token_mapping.get(&*price_data.id) {
+            let accepted = match validate_pyth_price(token, price_data, now) {
+                Some(accepted) => accepted,
+                None => continue,
+            };
+            let sample = PriceSample {
+                source: if accepted.is_ema { "pyth_ema" } else { "pyth" },
+                price: accepted.price(),
+                conf: Some(accepted.conf()),
+                publish_time: Some(accepted.publish_time),
+            };
+            result.insert(token.to_string(), sample);
+        }
+    }
+
+    result
+}
+
+pub async fn get_pyth_prices(chain_id: i64) -> Result<HashMap<String, PriceSample>, Box<dyn std::error::Error>> {
     let client = reqwest::Client::new();
-    let response = client.get(get_pyth_price_url()).send().await?;
+    let response = client.get(get_pyth_price_url(chain_id)).send().await?;
 
     if response.status() != reqwest::StatusCode::OK {
         eprintln!(
@@ -81,33 +92,15 @@ pub async fn get_pyth_prices() -> Result<HashMap<String, f64>, Box<dyn std::erro
 
     let response_data: PythResponse = response.json().await?;
 
-    for price_data in response_data.parsed.iter() {
-        if let Some(token) = pyth_id_to_token_mapping.get(&*price_data.id) {
-            let adjusted_price = (price_data.price.price.parse::<f64>().unwrap())
-                * (10f64).powi(price_data.price.expo);
-            result.insert(token.to_string(), adjusted_price);
-
-            if token.to_string() == "BTC" {
-                result.insert(
-                    "WBTC".to_string(),
-                    price_data.price.price.parse::<f64>().unwrap() / 10f64.powi(8),
-                );
-            }
-            if token.to_string() == "ETH" {
-                result.insert(
-                    "WETH".to_string(),
-                    price_data.price.price.parse::<f64>().unwrap() / 10f64.powi(8),
-                );
-            }
-        }
-    }
-
-    Ok(result)
+    Ok(parse_pyth_response(chain_id, &response_data))
 }
 
-pub async fn fetch_all_parcl_ids() -> Result<Vec<i64>, Box<dyn std::error::Error>> {
-    let url = "https://parcl-api.com/v1/metadata/parcl-ids";
-    let client = reqwest::Client::new();
+// `base_url` is PARCL_BASE_URL outside of tests.
+pub async fn fetch_all_parcl_ids(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> Result<Vec<i64>, Box<dyn std::error::Error>> {
+    let url = format!("{}/v1/metadata/parcl-ids", base_url);
 
     let response = client
         .get(url)
@@ -134,82 +127,70 @@ pub struct Param {
     pub id: String,
 }
 
-pub async fn fetch_parcl_details(
-    parcl_ids: Vec<String>,
-) -> Result<HashMap<String, ParclDetails>, Box<dyn std::error::Error>> {
-    let url = "https://parcl-api.com/v1/real-estate-data/parcl-info";
-    let client = reqwest::Client::new();
-    let mut parcl_map: HashMap<String, ParclDetails> = HashMap::new();
-
-    for parcl_id in parcl_ids {
-        let params = Param {
-            id: parcl_id.clone(),
-        };
-
-        let response = client
-            .get(url)
-            .query(&params)
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36")
-            .header("Origin", "https://app.parcl.co")
-            .header("Referer", "https://app.parcl.co/")
-            .send()
-            .await?;
-
-        if response.status() != reqwest::StatusCode::OK {
-            eprintln!(
-                "Failed to retrieve data for Parcel ID {}. Status code: {}",
-                parcl_id,
-                response.status()
-            );
-            continue;
-        }
+pub async fn fetch_parcl_info(
+    client: &reqwest::Client,
+    base_url: &str,
+    parcl_id: &str,
+) -> Result<Info, Box<dyn std::error::Error>> {
+    let url = format!("{}/v1/real-estate-data/parcl-info", base_url);
+    let params = Param {
+        id: parcl_id.to_string(),
+    };
 
-        let response_data: ParclResponse = response.json().await?;
-        let parcl_details = ParclDetails {
-            parcl_id: parcl_id.clone(),
-            name: response_data.info.name,
-            current_price: response_data.info.current_price.to_string(),
-        };
+    let response = client
+        .get(url)
+        .query(&params)
+        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36")
+        .header("Origin", "https://app.parcl.co")
+        .header("Referer", "https://app.parcl.co/")
+        .send()
+        .await?;
 
-        parcl_map.insert(parcl_id, parcl_details);
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(format!(
+            "Failed to retrieve data for Parcel ID {}. Status code: {}",
+            parcl_id,
+            response.status()
+        )
+        .into());
     }
 
-    Ok(parcl_map)
+    let response_data: ParclResponse = response.json().await?;
+    Ok(response_data.info)
 }
 
-pub async fn gathertokenprices() -> Result<HashMap<String, Vec<f64>>, Box<dyn std::error::Error>> {
-    let responses = match get_pyth_prices().await {
-        Ok(data) => data,
-        Err(e) => {
-            panic!("Error getting Pyth prices: {}", e);
-        }
-    };
-
-    let mut prices: HashMap<String, Vec<f64>> = match PRICES_MAPPINGS.get(ENV.NETWORK.as_str()) {
+// Groups the latest Pyth and exchange prices into per-token sample lists.
+pub fn gathertokenprices(
+    chain_id: i64,
+    pyth_prices: &HashMap<String, PriceSample>,
+    exchange_prices: &[(&'static str, HashMap<String, f64>)],
+) -> Result<HashMap<String, Vec<PriceSample>>, Box<dyn std::error::Error>> {
+    let mut prices: HashMap<String, Vec<PriceSample>> = match PRICES_MAPPINGS.get(&chain_id) {
         Some(map) => map
             .iter()
             .map(|(k, v)| (k.to_string(), v.clone()))
             .collect(), // Convert keys to `String`
         None => {
-            eprintln!("No price mappings found for network: {}", ENV.NETWORK);
+            eprintln!("No price mappings found for chain: {}", chain_id);
             return Err("Network not found".into());
         }
     };
 
-    const BITLAYER_NOT_SUPPORTED_BLUE_CHIPS: [&str; 1] = ["ETH"];
-
-    for (key, value) in responses.iter() {
-        let key_str = key.to_string(); // Convert borrowed key to owned `String`
+    for (key, value) in pyth_prices.iter() {
+        if let Some(vec) = prices.get_mut(key) {
+            vec.push(value.clone());
+        }
+    }
 
-        if ENV.NETWORK == "bitlayer_testnet" {
-            if !BITLAYER_NOT_SUPPORTED_BLUE_CHIPS.contains(&key_str.as_str()) {
-                if let Some(vec) = prices.get_mut(&key_str) {
-                    vec.push(*value);
-                }
-            }
-        } else {
-            if let Some(vec) = prices.get_mut(&key_str) {
-                vec.push(*value);
+    for (source, source_prices) in exchange_prices.iter() {
+        for (key, value) in source_prices.iter() {
+            if let Some(vec) = prices.get_mut(key) {
+                vec.push(PriceSample {
+                    source,
+                    price: *value,
+                    conf: None,
+                    publish_time: None,
+                });
             }
         }
     }
@@ -217,170 +198,210 @@ pub async fn gathertokenprices() -> Result<HashMap<String, Vec<f64>>, Box<dyn st
     Ok(prices)
 }
 
-pub async fn get_token_prices() -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
-    let prices = match gathertokenprices().await {
-        Ok(data) => data,
-        Err(e) => {
-            panic!("Error getting Pyth prices: {}", e);
-        }
-    };
+pub fn get_token_prices(
+    chain_id: i64,
+    pyth_prices: &HashMap<String, PriceSample>,
+    exchange_prices: &[(&'static str, HashMap<String, f64>)],
+) -> Result<HashMap<String, AggregatedPrice>, Box<dyn std::error::Error>> {
+    let prices = gathertokenprices(chain_id, pyth_prices, exchange_prices)?;
 
-    let mut rng = rand::rng();
-    let mut result: HashMap<String, f64> = HashMap::new();
+    let mut result: HashMap<String, AggregatedPrice> = HashMap::new();
 
     for (key, token_prices) in prices.iter() {
-        let mut token_prices = token_prices.clone();
-
-        if token_prices.len() > 2 {
-            token_prices.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-            token_prices.pop();
-            token_prices.remove(0);
-        }
-
-        let mut price_sum: f64 = 0.0;
-        let mut weight_sum: u32 = 0;
-
-        for price in &token_prices {
-            let weight = rng.random_range(10..20);
-            weight_sum += weight;
-            price_sum += price * weight as f64;
-        }
-
-        if price_sum == 0.0 {
-            println!("Token skipped: {}", key);
-            continue;
+        match aggregate_prices(ENV.AGGREGATION_STRATEGY, token_prices, &ENV.SOURCE_WEIGHTS) {
+            Some(mut aggregated) => {
+                if let Some(asset) = REGISTRY.asset(chain_id, key) {
+                    aggregated.spread = compute_spread(&asset.spread, aggregated.price, token_prices);
+                }
+                result.insert(key.clone(), aggregated);
+            }
+            None => println!("Token skipped: {}", key),
         }
-
-        result.insert(key.clone(), price_sum / weight_sum as f64);
     }
 
+    derive_asset_prices(REGISTRY.assets(chain_id), &mut result);
+
     Ok(result)
 }
 
-pub async fn gettokenpricesfromdb(db: &DatabaseConnection) -> Result<HashMap<&str, f32>, DbErr> {
-    let mut grouped_tokens = HashMap::new();
-    let mut result = HashMap::new();
-    let mut supportedfinaltokens = Vec::new();
-    let supportedtokens = match SUPPORTED_TOKENS.get(&ENV.NETWORK) {
-        Some(data) => data.clone(),
-        None => panic!("Error : Cannot get tokens"),
-    };
-
-    let supportedrealestatetokens = vec![
-        "CLT", "DEN", "MIA", "TPA", "MIAB", "NYC", "LAX", "SAN", "SOLB", "SFO", "LAS", "PIT",
-        "PHL", "AUS", "DFW", "IAH", "ATL", "SEA", "PHX", "CHI", "BOS", "PDX", "WDC", "BKN", "USA",
-        "PARIS", "LCY", "CHIR", "DENR", "USDR",
-    ];
-
-    for token in supportedtokens {
-        if !supportedrealestatetokens.contains(&token) {
-            supportedfinaltokens.push(token);
+// Half-width of the ticker band for `price` under `policy`, from the samples
+// the price was aggregated from.
+pub fn compute_spread(policy: &SpreadPolicy, price: f64, samples: &[PriceSample]) -> f64 {
+    let bps_of_price = |bps: f64| price * bps / 10_000.0;
+
+    let spread = match policy {
+        SpreadPolicy::None => 0.0,
+        SpreadPolicy::Bps { bps } => bps_of_price(*bps),
+        SpreadPolicy::PythConf { multiplier, min_bps } => {
+            let conf = samples
+                .iter()
+                .filter(|sample| sample.source.starts_with("pyth"))
+                .find_map(|sample| sample.conf)
+                .unwrap_or(0.0);
+            f64::max(conf * multiplier, bps_of_price(*min_bps))
         }
-    }
-    let mut token_addresses = Vec::new();
-
-    for token_symbol in supportedfinaltokens.clone() {
-        let tokenaddress = match SYMBOL_TO_ADDRESS_MAPPING.get(token_symbol) {
-            Some(data) => data,
-            None => panic!("Error : Cannot get address"),
-        };
-        token_addresses.push(tokenaddress.to_string());
-    }
-
-    let mut real_estate_token_address = Vec::new();
-
-    if ENV.NETWORK == "bitlayer_testnet".to_string() {
-        for token_symbol in supportedrealestatetokens.clone() {
-            let realestatetoken = match SYMBOL_TO_ADDRESS_MAPPING.get(token_symbol) {
-                Some(data) => data,
-                None => panic!("Error : Cannot get token symbol"),
-            };
-            real_estate_token_address.push(realestatetoken.to_string());
+        SpreadPolicy::SourceDispersion { multiplier, min_bps } => {
+            let valid = samples.iter().map(|sample| sample.price).filter(|price| price.is_finite());
+            let (low, high) = valid.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), price| {
+                (low.min(price), high.max(price))
+            });
+            let dispersion = if high >= low { (high - low) / 2.0 } else { 0.0 };
+            f64::max(dispersion * multiplier, bps_of_price(*min_bps))
         }
-    }
-    let chainid = match CHAINID_MAP.get(&ENV.NETWORK) {
-        Some(data) => data,
-        None => panic!("Error : Cannot get chainid"),
     };
 
-    // Get regular token prices (1m period)
-    let tokens_data = PriceCandle::find()
-        .filter(price_candle::Column::Token.is_in(token_addresses.clone()))
-        .filter(price_candle::Column::Period.eq("1m"))
-        .filter(price_candle::Column::ChainId.eq(chainid.clone()))
-        .order_by_desc(price_candle::Column::Timestamp)
-        .all(db)
-        .await;
+    if spread.is_finite() { spread.max(0.0) } else { 0.0 }
+}
 
-    // Group by token and get latest close price
-    let tokens_data = match tokens_data {
-        Ok(data) => data,
-        Err(_) => panic!("Error : Cannot get data from DB"),
+fn compute_derived_price(
+    derived: &DerivedPrice,
+    prices: &HashMap<String, AggregatedPrice>,
+) -> Option<AggregatedPrice> {
+    let inputs = derived
+        .inputs()
+        .iter()
+        .map(|symbol| prices.get(*symbol))
+        .collect::<Option<Vec<&AggregatedPrice>>>()?;
+    let first = inputs.first()?;
+
+    let price = match derived {
+        DerivedPrice::Alias { .. } => first.price,
+        DerivedPrice::Ratio { .. } => {
+            let denominator = inputs.get(1)?.price;
+            if denominator == 0.0 {
+                return None;
+            }
+            first.price / denominator
+        }
+        DerivedPrice::Product { .. } => inputs.iter().map(|input| input.price).product(),
     };
 
-    for candle in tokens_data {
-        grouped_tokens
-            .entry(candle.token.clone()) // Use token as key
-            .or_insert(candle.close); // Store just the close price
-    }
+    let mut sources: Vec<&'static str> = inputs
+        .iter()
+        .flat_map(|input| input.sources.iter().copied())
+        .collect();
+    sources.sort();
+    sources.dedup();
+
+    // Relative spreads of the inputs add up, which is exact for an alias and
+    // a first-order bound for ratios and products.
+    let relative_spread: f64 = inputs
+        .iter()
+        .filter(|input| input.price > 0.0)
+        .map(|input| input.spread / input.price)
+        .sum();
+
+    Some(AggregatedPrice {
+        price,
+        strategy: first.strategy,
+        sources,
+        spread: price * relative_spread,
+    })
+}
 
-    // Map to token symbols
-    for (token_addr, close) in grouped_tokens {
-        if let Some(index) = token_addresses.iter().position(|x| x == &token_addr) {
-            if let Some(token_symbol) = supportedfinaltokens.get(index) {
-                result.insert(*token_symbol, close);
+// Adds the prices of derived assets, computed from the normalized aggregated
+// prices. Derived assets may build on each other, so passes repeat until no
+// further price can be computed.
+pub fn derive_asset_prices(assets: &[AssetRecord], prices: &mut HashMap<String, AggregatedPrice>) {
+    let mut pending: Vec<(&str, &DerivedPrice, &SpreadPolicy)> = assets
+        .iter()
+        .filter_map(|asset| Some((asset.symbol.as_str(), asset.derived.as_ref()?, &asset.spread)))
+        .collect();
+
+    loop {
+        let pending_before = pending.len();
+        pending.retain(|(symbol, derived, policy)| match compute_derived_price(derived, prices) {
+            Some(mut aggregated) => {
+                // Derived assets have no samples of their own, so their policy
+                // only widens the inherited spread to its basis-point floor.
+                aggregated.spread = aggregated.spread.max(compute_spread(policy, aggregated.price, &[]));
+                prices.insert(symbol.to_string(), aggregated);
+                false
             }
+            None => true,
+        });
+        if pending.is_empty() || pending.len() == pending_before {
+            break;
         }
     }
 
-    // Get real estate token prices (1d period)
-    let realestatedata = PriceCandle::find()
-        .filter(price_candle::Column::Token.is_in(real_estate_token_address.clone()))
-        .filter(price_candle::Column::Period.eq("1d"))
-        .filter(price_candle::Column::ChainId.eq(chainid.clone()))
-        .order_by_desc(price_candle::Column::Timestamp)
-        .all(db)
-        .await;
-
-    let real_estate_data = match realestatedata {
-        Ok(data) => data,
-        Err(_) => panic!("Error: Cannot get data form DB"),
-    };
-
-    // Group by token and get latest close price
-    let mut grouped_real_estate = HashMap::new();
+    for (symbol, derived, _) in pending {
+        println!("Derived token skipped: {} (inputs {:?})", symbol, derived.inputs());
+    }
+}
 
-    for candle in real_estate_data {
-        grouped_real_estate
-            .entry(candle.token.clone())
-            .or_insert(candle.close);
+// Latest stored price of every listed token, keyed by symbol, for when the
+// cache has none. Each token is read from its asset class's latest period
+// (e.g. 1d for real estate, which only gets daily prices).
+pub async fn gettokenpricesfromdb(db: &DatabaseConnection, chain_id: i64) -> Result<HashMap<String, LatestPrice>, DbErr> {
+    let mut result = HashMap::new();
+    let listed: Vec<(&AssetRecord, &str)> = REGISTRY
+        .listed_assets(chain_id)
+        .map(|asset| {
+            let period = REGISTRY.class_config(asset.asset_class).latest_period.as_str();
+            (asset, period)
+        })
+        .collect();
+    let mut periods: Vec<&str> = listed.iter().map(|(_, period)| *period).collect();
+    periods.sort_unstable();
+    periods.dedup();
+    if periods.is_empty() {
+        return Ok(result);
     }
 
-    // Map to token symbols
-    for (token_addr, close) in grouped_real_estate {
-        if let Some(index) = real_estate_token_address.iter().position(|x| x == &token_addr) {
-            if let Some(token_symbol) = supportedrealestatetokens.get(index) {
-                result.insert(token_symbol, close);
-            }
+    // Newest candle per token and period, one index scan per group
+    let placeholders = (0..periods.len())
+        .map(|index| format!("${}", index + 2))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut values: Vec<Value> = vec![chain_id.into()];
+    values.extend(periods.iter().map(|period| Value::from(*period)));
+    let latest_candles = PriceCandle::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"SELECT DISTINCT ON (token, period) * FROM price_candle
+               WHERE chain_id = $1 AND period IN ({})
+               ORDER BY token, period, timestamp DESC"#,
+                placeholders
+            ),
+            values,
+        ))
+        .all(db)
+        .await?;
+
+    for (asset, period) in listed {
+        let Some(token_address) = &asset.address else {
+            continue;
+        };
+        let candle = latest_candles
+            .iter()
+            .find(|candle| &candle.token == token_address && candle.period == period);
+
+        if let Some(candle) = candle {
+            result.insert(
+                asset.symbol.clone(),
+                LatestPrice {
+                    price: candle.close,
+                    spread: candle.spread,
+                    updated_at: candle.timestamp.max(0) as u64,
+                },
+            );
         }
     }
 
     Ok(result)
 }
 
-pub async fn calculate_price_decimals(price: f32) -> Option<usize> {
-    if price > 1.0 {
-        return Some(PRICE_DECIMALS);
+pub async fn calculate_price_decimals(price: Decimal) -> Option<usize> {
+    if price > Decimal::ONE {
+        Some(PRICE_DECIMALS)
     } else {
-        let price_string = price.to_string();
+        // Decimal never formats in exponent notation, so counting the zeroes
+        // after the point is enough.
+        let price_string = price.normalize().to_string();
 
-        if let Some(starting_index_exponential) = price_string.find("e") {
-            let exponent_char = price_string.chars().nth(starting_index_exponential + 2)?;
-
-            let exponent_digit = exponent_char.to_digit(10)? as usize;
-
-            Some(exponent_digit - 1 + PRICE_DECIMALS)
-        } else if let Some(starting_index) = price_string.find(".") {
+        if let Some(starting_index) = price_string.find(".") {
             let mut trailing_zeroes = 0;
             for c in price_string[starting_index + 1..].chars() {
                 match c {
@@ -391,43 +412,221 @@ pub async fn calculate_price_decimals(price: f32) -> Option<usize> {
 
             Some(trailing_zeroes + PRICE_DECIMALS)
         } else {
-            return Some(0);
+            Some(0)
         }
     }
 }
 
-pub async fn get_token_prices_filtered(db: &DatabaseConnection) -> Vec<AssetPricingInfo2> {
-    let token_prices = match gettokenpricesfromdb(db).await {
-        Ok(data) => data,
-        Err(_) => panic!("Error : Cannot get token prices form DB"),
-    };
+// Converts a price into the integer the contracts expect for an asset with
+// `asset_decimals` decimals, i.e. price * 10^asset_decimals. The price is first
+// rounded half away from zero to PRECISION decimal places (or fewer if the
+// asset has fewer decimals), so the result is exact for any decimals value.
+pub fn scale_price_to_decimals(price: Decimal, asset_decimals: u64) -> BigInt {
+    let places = (PRECISION as u64).min(asset_decimals) as u32;
+    let rounded = price.round_dp_with_strategy(places, RoundingStrategy::MidpointAwayFromZero);
+
+    // rounded = mantissa / 10^scale with scale <= places <= asset_decimals.
+    let exponent = asset_decimals - rounded.scale() as u64;
+    BigInt::from(rounded.mantissa()) * BigInt::from(10).pow(exponent as u32)
+}
+
+// Ticker data for every supported token, read from the latest-price cache.
+// The first request of a chain seeds the cache from the database; tokens with
+// no stored price are not looked up again.
+pub async fn get_token_prices_filtered(
+    db: &DatabaseConnection,
+    cache: &LatestPriceCache,
+    chain_id: i64,
+) -> Result<Vec<AssetPricingInfo2>, ApiError> {
+    if !cache.is_seeded(chain_id) {
+        let stored_prices = gettokenpricesfromdb(db, chain_id).await?;
+        cache.seed(chain_id, &stored_prices);
+    }
+    let token_prices = cache.chain_prices(chain_id);
 
     let mut token_prices_array = Vec::new();
+    let (symbol_to_decimal, symbol_to_address) = match (
+        SYMBOL_TO_DECIMAL_MAPPING.get(&chain_id),
+        SYMBOL_TO_ADDRESS_MAPPING.get(&chain_id),
+    ) {
+        (Some(decimals), Some(addresses)) => (decimals, addresses),
+        _ => return Err(ApiError::Internal(format!("Chain {} not found in token mappings", chain_id))),
+    };
 
-    let timestamp = Utc::now();
-    for (token, price) in token_prices {
-        let asset_price: i32 = (price * 10f32.powi(PRECISION)).round() as i32;
-        let asset_price_bigint = BigInt::from(asset_price);
-        let asset_decimals = match SYMBOL_TO_DECIMAL_MAPPING.get(token) {
-            Some(&decimals) => decimals,
-            None => panic!("Error: Asset not found in mapping"),
+    let now = unix_now();
+    for (token, latest) in token_prices {
+        let (asset_decimals, token_address) = match (symbol_to_decimal.get(&token), symbol_to_address.get(&token)) {
+            (Some(&decimals), Some(address)) => (decimals, address),
+            // Cached price-only feeds (e.g. ETH) are not listed tokens
+            _ => continue,
         };
 
-        let power = asset_decimals as i32 - PRECISION as i32;
-        let scale_factor = BigInt::from(10).pow(power as u32);
-
-        let token_price = asset_price_bigint * scale_factor;
+        let price = latest.price;
+        let asset = REGISTRY.asset(chain_id, &token);
+        let spread_policy = asset
+            .map(|asset| asset.spread.as_str())
+            .unwrap_or(SpreadPolicy::None.as_str());
+        let age_seconds = latest.age_seconds(now);
+        let stale = asset.is_some_and(|asset| {
+            age_seconds > REGISTRY.class_config(asset.asset_class).staleness_secs
+        });
+        let schedule = asset.map(|asset| TRADING_CALENDAR.for_asset(asset));
+        let is_market_open = schedule.is_none_or(|schedule| schedule.is_open(now as i64));
+        let last_trade_at = match schedule {
+            Some(schedule) => schedule.last_open_at(latest.updated_at as i64),
+            None => Some(latest.updated_at as i64),
+        };
+        // Prices written before spreads were stored have none.
+        let spread = latest.spread.unwrap_or_default();
+        let min_price = (price - spread).max(Decimal::ZERO);
+        let max_price = price + spread;
 
         let token_prices_filtered = AssetPricingInfo2 {
-            token_address: SYMBOL_TO_ADDRESS_MAPPING.get(token).unwrap().to_string(),
+            token_address: token_address.to_string(),
             token_symbol: token.to_string(),
-            min_price: Some(token_price.to_string()),
-            max_price: Some(token_price.to_string()),
-            updated_at: timestamp,
-            price_decimals: calculate_price_decimals(price).await.unwrap() as f32,
+            min_price: Some(scale_price_to_decimals(min_price, asset_decimals).to_string()),
+            max_price: Some(scale_price_to_decimals(max_price, asset_decimals).to_string()),
+            updated_at: Utc
+                .timestamp_opt(latest.updated_at as i64, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            age_seconds,
+            stale,
+            is_market_open,
+            last_trade_at: last_trade_at
+                .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single()),
+            price_decimals: calculate_price_decimals(price).await.unwrap_or(PRICE_DECIMALS) as f32,
+            spread_policy: spread_policy.to_string(),
         };
 
         token_prices_array.push(token_prices_filtered);
     }
-    token_prices_array
+    Ok(token_prices_array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Largest mantissa a Decimal holds (96 bits).
+    const MAX_MANTISSA: i128 = (1 << 96) - 1;
+
+    // Adds one to a string of decimal digits.
+    fn increment_digits(digits: &str) -> String {
+        let mut digits: Vec<u8> = digits.bytes().collect();
+        for digit in digits.iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                return String::from_utf8(digits).unwrap();
+            }
+        }
+        format!("1{}", String::from_utf8(digits).unwrap())
+    }
+
+    // Reference implementation on the decimal string: round the fraction half
+    // away from zero to min(PRECISION, decimals) digits, then move the point
+    // `decimals` places to the right.
+    fn reference_scale(price: Decimal, decimals: u64) -> String {
+        let text = price.to_string();
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, text.as_str()),
+        };
+        let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let places = (PRECISION as u64).min(decimals) as usize;
+
+        let (kept, dropped) = fraction.split_at(places.min(fraction.len()));
+        let mut digits = format!("{}{}", integer, kept);
+        if dropped.as_bytes().first().is_some_and(|digit| *digit >= b'5') {
+            digits = increment_digits(&digits);
+        }
+        digits.push_str(&"0".repeat(decimals as usize - kept.len()));
+
+        let digits = digits.trim_start_matches('0');
+        match (digits.is_empty(), negative) {
+            (true, _) => "0".to_string(),
+            (false, true) => format!("-{}", digits),
+            (false, false) => digits.to_string(),
+        }
+    }
+
+    fn scaled(price: &str, decimals: u64) -> String {
+        scale_price_to_decimals(price.parse().unwrap(), decimals).to_string()
+    }
+
+    #[tokio::test]
+    async fn tickers_query_the_database_once_per_chain() {
+        // Every query fails on a disconnected database.
+        let db = DatabaseConnection::Disconnected;
+        let cache = LatestPriceCache::new();
+        let chain_id = REGISTRY.networks["bitlayer_testnet"].chain_id;
+        assert!(get_token_prices_filtered(&db, &cache, chain_id).await.is_err());
+
+        // Once seeded, tokens without a price don't trigger another query.
+        cache.seed(chain_id, &HashMap::new());
+        let tickers = get_token_prices_filtered(&db, &cache, chain_id).await.unwrap();
+        assert!(tickers.is_empty());
+
+        cache.update(
+            chain_id,
+            "WBTC",
+            LatestPrice {
+                price: "67000.5".parse().unwrap(),
+                spread: None,
+                updated_at: unix_now(),
+            },
+        );
+        let tickers = get_token_prices_filtered(&db, &cache, chain_id).await.unwrap();
+        assert_eq!(tickers.len(), 1);
+        assert_eq!(tickers[0].token_symbol, "WBTC");
+    }
+
+    #[test]
+    fn scales_fixed_prices() {
+        assert_eq!(scaled("1.5", 18), "1500000000000000000");
+        assert_eq!(scaled("0.00001234", 8), "1234");
+        // Rounded to PRECISION places before scaling.
+        assert_eq!(scaled("0.123456789012345", 18), "123456789000000000");
+        assert_eq!(scaled("0.12345678905", 18), "123456789100000000");
+        // Zero decimals rounds to a whole number.
+        assert_eq!(scaled("2.5", 0), "3");
+        assert_eq!(scaled("-2.5", 0), "-3");
+        assert_eq!(scaled("0.49", 0), "0");
+        // Very large prices stay exact.
+        assert_eq!(
+            scaled("79228162514264337593543950335", 30),
+            format!("79228162514264337593543950335{}", "0".repeat(30))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn matches_string_shift(
+            mantissa in -MAX_MANTISSA..=MAX_MANTISSA,
+            scale in 0u32..=28,
+            decimals in 0u64..=40,
+        ) {
+            let price = Decimal::from_i128_with_scale(mantissa, scale);
+            prop_assert_eq!(
+                scale_price_to_decimals(price, decimals).to_string(),
+                reference_scale(price, decimals)
+            );
+        }
+
+        #[test]
+        fn matches_string_shift_for_small_prices(
+            mantissa in -1_000_000_000i128..=1_000_000_000,
+            scale in 0u32..=28,
+            decimals in prop_oneof![Just(0u64), Just(PRECISION as u64), (PRECISION as u64 + 1)..=40],
+        ) {
+            let price = Decimal::from_i128_with_scale(mantissa, scale);
+            prop_assert_eq!(
+                scale_price_to_decimals(price, decimals).to_string(),
+                reference_scale(price, decimals)
+            );
+        }
+    }
 }
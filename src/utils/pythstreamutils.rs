@@ -0,0 +1,253 @@
+use crate::configs::envconfig::ENV;
+use crate::utils::aggregationutils::PriceSample;
+use crate::utils::helpersutils::PYTH_ID;
+use crate::utils::pricesutils::parse_pyth_response;
+use crate::utils::responseinterfaceutils::PythResponse;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const PYTH_STREAM_MIN_BACKOFF_MS: u64 = 1000;
+const PYTH_STREAM_MAX_BACKOFF_MS: u64 = 60_000;
+// Hermes publishes several updates per second; a silent connection this long
+// is treated as dead and reconnected.
+const PYTH_STREAM_IDLE_TIMEOUT_SECS: u64 = 30;
+
+pub fn get_pyth_stream_url(chain_id: i64) -> String {
+    let mut pyth_url = format!("{}/v2/updates/price/stream?", ENV.HERMES_BASE_URL);
+    if let Some(pyth_ids) = PYTH_ID.get(&chain_id) {
+        for (i, id) in pyth_ids.iter().enumerate() {
+            if i > 0 {
+                pyth_url.push('&');
+            }
+            pyth_url.push_str(&format!("ids[]={}", id));
+        }
+    }
+    pyth_url
+}
+
+// Incremental server-sent-events decoder. Bytes are pushed as they arrive and
+// the `data` payload of every completed event is returned. Bytes are buffered
+// until a line is complete, so a UTF-8 character split across chunks is
+// decoded whole.
+#[derive(Default)]
+pub struct SseDecoder {
+    // Bytes of the line still being received.
+    line: Vec<u8>,
+    // `data` lines of the event still being received.
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.line.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.line.iter().position(|byte| *byte == b'\n') {
+            let raw: Vec<u8> = self.line.drain(..=end).collect();
+            let raw = raw.strip_suffix(b"\n").unwrap_or(&raw);
+            let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+            let line = String::from_utf8_lossy(raw);
+
+            if line.is_empty() {
+                // A blank line completes the event.
+                let data = self.data.join("\n");
+                self.data.clear();
+                if !data.is_empty() {
+                    events.push(data);
+                }
+            } else if let Some(data) = line.strip_prefix("data:") {
+                self.data.push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+        }
+        events
+    }
+}
+
+// Reads one SSE connection until it ends or errors, forwarding every price
+// update. Returns Ok once at least one update was delivered so the caller can
+// reset its backoff.
+async fn read_pyth_stream(
+    client: &reqwest::Client,
+    url: &str,
+    chain_id: i64,
+    sender: &mpsc::Sender<HashMap<String, PriceSample>>,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let mut response = client
+        .get(url)
+        .header("Accept", "text/event-stream")
+        .send()
+        .await?;
+
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(format!("Pyth stream returned status {}", response.status()).into());
+    }
+
+    let mut decoder = SseDecoder::default();
+    let mut delivered = false;
+
+    loop {
+        let chunk = tokio::time::timeout(
+            Duration::from_secs(PYTH_STREAM_IDLE_TIMEOUT_SECS),
+            response.chunk(),
+        )
+        .await
+        .map_err(|_| "Pyth stream idle timeout")??;
+
+        let chunk = match chunk {
+            Some(chunk) => chunk,
+            None => return Ok(delivered),
+        };
+
+        for data in decoder.push(&chunk) {
+            let update: PythResponse = match serde_json::from_str(&data) {
+                Ok(update) => update,
+                Err(e) => {
+                    eprintln!("Error parsing Pyth stream event: {}", e);
+                    continue;
+                }
+            };
+            let prices = parse_pyth_response(chain_id, &update);
+            if prices.is_empty() {
+                continue;
+            }
+            if sender.send(prices).await.is_err() {
+                // The submitter is gone, nothing left to feed.
+                return Ok(delivered);
+            }
+            delivered = true;
+        }
+    }
+}
+
+// Consumes the Hermes price stream for `chain_id` forever, reconnecting with
+// exponential backoff. Updates are sent as token -> Pyth sample.
+pub async fn stream_pyth_prices(chain_id: i64, sender: mpsc::Sender<HashMap<String, PriceSample>>) {
+    let client = reqwest::Client::new();
+    let url = get_pyth_stream_url(chain_id);
+    let mut backoff_ms = PYTH_STREAM_MIN_BACKOFF_MS;
+
+    while !sender.is_closed() {
+        match read_pyth_stream(&client, &url, chain_id, &sender).await {
+            Ok(true) => {
+                println!("Pyth stream for chain {} closed, reconnecting", chain_id);
+                backoff_ms = PYTH_STREAM_MIN_BACKOFF_MS;
+            }
+            Ok(false) => {
+                eprintln!("Pyth stream for chain {} closed without updates", chain_id);
+            }
+            Err(e) => {
+                eprintln!("Pyth stream error for chain {}: {}", chain_id, e);
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+        backoff_ms = (backoff_ms * 2).min(PYTH_STREAM_MAX_BACKOFF_MS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::helpersutils::PYTH_ID_TO_TOKEN_MAPPING;
+    use chrono::Utc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn decodes_utf8_split_across_chunks() {
+        let mut decoder = SseDecoder::default();
+        let event = "data: {\"name\":\"caf\u{e9}\"}\n\n".as_bytes();
+        let split = event.iter().position(|byte| *byte == 0xc3).unwrap() + 1;
+
+        assert!(decoder.push(&event[..split]).is_empty());
+        assert_eq!(decoder.push(&event[split..]), vec!["{\"name\":\"caf\u{e9}\"}"]);
+    }
+
+    #[test]
+    fn decodes_crlf_and_multiline_events() {
+        let mut decoder = SseDecoder::default();
+        let events = decoder.push(b": keep-alive\r\nid: 1\r\ndata: first\r\ndata: second\r\n\r\ndata: th");
+        assert_eq!(events, vec!["first\nsecond"]);
+        assert!(decoder.push(b"ird\r").is_empty());
+        assert_eq!(decoder.push(b"\n\n"), vec!["third"]);
+        // Events without data are skipped.
+        assert!(decoder.push(b"event: ping\n\n").is_empty());
+    }
+
+    // Serves `chunks` as one SSE response, pausing between chunks so they
+    // arrive separately, then closes the connection.
+    async fn serve_sse(chunks: Vec<Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = socket.read(&mut buffer).await.unwrap();
+                if read == 0 {
+                    return;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            for chunk in chunks {
+                socket.write_all(&chunk).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+        format!("http://{}/v2/updates/price/stream", address)
+    }
+
+    fn pyth_event(pyth_id: &str, price: &str, publish_time: u64) -> Vec<u8> {
+        let update = serde_json::json!({
+            "binary": {"encoding": "hex", "data": []},
+            "parsed": [{
+                "id": pyth_id,
+                "price": {"price": price, "conf": "1000", "expo": -8, "publish_time": publish_time},
+                "ema_price": {"price": price, "conf": "1000", "expo": -8, "publish_time": publish_time},
+                "metadata": {"slot": 1, "proof_available_time": publish_time, "prev_publish_time": publish_time}
+            }]
+        });
+        format!("data: {}\n\n", update).into_bytes()
+    }
+
+    #[tokio::test]
+    async fn forwards_updates_from_stream() {
+        let (chain_id, pyth_id, token) = PYTH_ID_TO_TOKEN_MAPPING
+            .iter()
+            .find_map(|(chain_id, ids)| {
+                let (pyth_id, token) = ids.iter().next()?;
+                Some((*chain_id, *pyth_id, *token))
+            })
+            .expect("No Pyth feed in the registry");
+        let now = Utc::now().timestamp() as u64;
+
+        // The first event is cut inside a line, the second arrives whole
+        // together with a malformed one.
+        let first = pyth_event(pyth_id, "6701234000000", now);
+        let (head, tail) = first.split_at(first.len() / 2);
+        let mut rest = tail.to_vec();
+        rest.extend_from_slice(b"data: not json\n\n");
+        rest.extend_from_slice(&pyth_event(pyth_id, "6702000000000", now));
+        let url = serve_sse(vec![head.to_vec(), rest]).await;
+
+        let (sender, mut receiver) = mpsc::channel(8);
+        let delivered = read_pyth_stream(&reqwest::Client::new(), &url, chain_id, &sender)
+            .await
+            .unwrap();
+        assert!(delivered);
+
+        let prices: Vec<f64> = std::iter::from_fn(|| receiver.try_recv().ok())
+            .map(|update| update[token].price)
+            .collect();
+        assert_eq!(prices.len(), 2);
+        assert!((prices[0] - 67012.34).abs() < 1e-6);
+        assert!((prices[1] - 67020.0).abs() < 1e-6);
+    }
+}
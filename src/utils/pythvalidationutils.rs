@@ -0,0 +1,155 @@
+use crate::configs::envconfig::ENV;
+use crate::utils::responseinterfaceutils::Parsed;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PythRejectReason {
+    // publish_time is older than MAX_PRICE_INTERVAL.
+    Stale,
+    // conf / price is above MAX_CONF_RATIO.
+    WideConfidence,
+    // price or conf is not a valid positive number.
+    InvalidPrice,
+}
+
+impl PythRejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PythRejectReason::Stale => "stale",
+            PythRejectReason::WideConfidence => "wide_confidence",
+            PythRejectReason::InvalidPrice => "invalid_price",
+        }
+    }
+
+    fn counter(&self) -> &'static AtomicU64 {
+        match self {
+            PythRejectReason::Stale => &STALE_REJECTIONS,
+            PythRejectReason::WideConfidence => &WIDE_CONFIDENCE_REJECTIONS,
+            PythRejectReason::InvalidPrice => &INVALID_PRICE_REJECTIONS,
+        }
+    }
+}
+
+impl fmt::Display for PythRejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+static STALE_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+static WIDE_CONFIDENCE_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+static INVALID_PRICE_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+static EMA_FALLBACKS: AtomicU64 = AtomicU64::new(0);
+
+// Logs and counts one rejected update.
+pub fn record_pyth_rejection(token: &str, reason: PythRejectReason, detail: &str) {
+    let total = reason.counter().fetch_add(1, Ordering::Relaxed) + 1;
+    eprintln!(
+        "Rejected Pyth price for {} ({}: {}), {} {} rejections so far",
+        token, reason, detail, total, reason
+    );
+}
+
+// A Pyth price that passed validation, still in mantissa/exponent form.
+#[derive(Debug, Clone, Copy)]
+pub struct AcceptedPythPrice {
+    pub mantissa: f64,
+    pub expo: i32,
+    pub conf: f64,
+    pub publish_time: u64,
+    pub is_ema: bool,
+}
+
+impl AcceptedPythPrice {
+    pub fn price(&self) -> f64 {
+        self.mantissa * 10f64.powi(self.expo)
+    }
+
+    pub fn conf(&self) -> f64 {
+        self.conf * 10f64.powi(self.expo)
+    }
+}
+
+pub fn is_stale(publish_time: u64, now: u64) -> bool {
+    now.saturating_sub(publish_time) > ENV.MAX_PRICE_INTERVAL as u64
+}
+
+fn check_price(
+    price: &str,
+    conf: &str,
+    expo: i32,
+    publish_time: u64,
+    now: u64,
+) -> Result<AcceptedPythPrice, (PythRejectReason, String)> {
+    let mantissa = price.parse::<f64>().ok().filter(|price| *price > 0.0);
+    let conf_mantissa = conf.parse::<f64>().ok().filter(|conf| *conf >= 0.0);
+    let (mantissa, conf_mantissa) = match (mantissa, conf_mantissa) {
+        (Some(mantissa), Some(conf_mantissa)) => (mantissa, conf_mantissa),
+        _ => {
+            return Err((
+                PythRejectReason::InvalidPrice,
+                format!("price {:?}, conf {:?}", price, conf),
+            ))
+        }
+    };
+
+    if is_stale(publish_time, now) {
+        return Err((
+            PythRejectReason::Stale,
+            format!("published {}s ago", now.saturating_sub(publish_time)),
+        ));
+    }
+
+    // Both share the exponent, so the ratio can be taken on the mantissas.
+    let conf_ratio = conf_mantissa / mantissa;
+    if conf_ratio > ENV.MAX_CONF_RATIO {
+        return Err((
+            PythRejectReason::WideConfidence,
+            format!("conf/price {:.6} > {}", conf_ratio, ENV.MAX_CONF_RATIO),
+        ));
+    }
+
+    Ok(AcceptedPythPrice {
+        mantissa,
+        expo,
+        conf: conf_mantissa,
+        publish_time,
+        is_ema: false,
+    })
+}
+
+// Validates the spot price of one update, falling back to the EMA price when
+// USE_EMA_FALLBACK is set and the spot price was rejected. Every rejection is
+// logged and counted; returns None if nothing usable is left.
+pub fn validate_pyth_price(token: &str, price_data: &Parsed, now: u64) -> Option<AcceptedPythPrice> {
+    let spot = &price_data.price;
+    let rejection = match check_price(&spot.price, &spot.conf, spot.expo, spot.publish_time, now) {
+        Ok(accepted) => return Some(accepted),
+        Err(rejection) => rejection,
+    };
+    record_pyth_rejection(token, rejection.0, &rejection.1);
+
+    if !ENV.USE_EMA_FALLBACK {
+        return None;
+    }
+
+    let ema = &price_data.ema_price;
+    match check_price(&ema.price, &ema.conf, ema.expo as i32, ema.publish_time, now) {
+        Ok(accepted) => {
+            let total = EMA_FALLBACKS.fetch_add(1, Ordering::Relaxed) + 1;
+            println!(
+                "Using Pyth EMA price for {} ({} EMA fallbacks so far)",
+                token, total
+            );
+            Some(AcceptedPythPrice {
+                is_ema: true,
+                ..accepted
+            })
+        }
+        Err((reason, detail)) => {
+            record_pyth_rejection(token, reason, &format!("EMA {}", detail));
+            None
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -48,13 +50,6 @@ pub struct ParclIdResponse {
     pub ids: Vec<i64>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ParclDetails {
-    pub parcl_id: String,
-    pub name: String,
-    pub current_price: String,
-}
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ParclResponse {
     pub info: Info,
@@ -128,3 +123,103 @@ pub struct SalesHistory {
     pub date: String,
     pub sales: i64,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BinanceTicker {
+    pub symbol: String,
+    pub price: String,
+}
+
+// Error bodies carry the status fields but no data, so they are checked
+// before the full response is parsed.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KucoinStatus {
+    pub code: String,
+    #[serde(default)]
+    pub msg: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KucoinResponse {
+    pub code: String,
+    pub data: KucoinData,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KucoinData {
+    pub time: u64,
+    pub ticker: Vec<KucoinTicker>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KucoinTicker {
+    pub symbol: String,
+    pub last: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GateTicker {
+    pub currency_pair: String,
+    pub last: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BybitStatus {
+    pub ret_code: i64,
+    #[serde(default)]
+    pub ret_msg: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BybitResponse {
+    pub ret_code: i64,
+    pub ret_msg: String,
+    pub result: BybitResult,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BybitResult {
+    pub list: Vec<BybitTicker>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BybitTicker {
+    pub symbol: String,
+    pub last_price: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OkxStatus {
+    pub code: String,
+    #[serde(default)]
+    pub msg: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OkxResponse {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<OkxTicker>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxTicker {
+    pub inst_id: String,
+    pub last: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KrakenResponse {
+    pub error: Vec<String>,
+    pub result: Option<HashMap<String, KrakenTicker>>,
+}
+
+// Only the last trade closed array `c` = [price, lot volume] is used.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KrakenTicker {
+    pub c: Vec<String>,
+}
@@ -0,0 +1,188 @@
+use crate::configs::registryconfig::REGISTRY;
+use crate::utils::helpersutils::PERIOD_MAP;
+use crate::utils::interfaceutils::Price24HResponse;
+use rust_decimal::Decimal;
+use sea_orm::{DatabaseConnection, DbBackend, DbErr, FromQueryResult, Statement, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const STATS_24H_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+#[derive(Debug, FromQueryResult)]
+struct Rolling24hRow {
+    token: String,
+    period: String,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+}
+
+// Rolling 24h open/high/low/close and change for every listed token of the
+// chain that has candles in the window. Each token's window is built from the
+// candles of its asset class's latest period (1m, or 1d for real estate) in
+// one grouped query, so the cost does not grow with history.
+pub async fn get_rolling_24h_stats(
+    db: &DatabaseConnection,
+    chain_id: i64,
+) -> Result<Vec<Price24HResponse>, DbErr> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    rolling_24h_stats_at(db, chain_id, now).await
+}
+
+// The window of each period starts with the candle covering `now` - 24h, so
+// the open is the price 24h ago even for candles longer than a minute (a 1d
+// window spans yesterday's and today's candles).
+async fn rolling_24h_stats_at(
+    db: &DatabaseConnection,
+    chain_id: i64,
+    now: i64,
+) -> Result<Vec<Price24HResponse>, DbErr> {
+    let window_start = now - STATS_24H_WINDOW_SECS;
+
+    let mut periods: Vec<&str> = REGISTRY
+        .listed_assets(chain_id)
+        .map(|asset| REGISTRY.class_config(asset.asset_class).latest_period.as_str())
+        .collect();
+    periods.sort_unstable();
+    periods.dedup();
+    if periods.is_empty() {
+        return Ok(Vec::new());
+    }
+    // (period = $n AND timestamp > $n+1) per period, bounded by the start of
+    // the candle covering window_start.
+    let mut values: Vec<Value> = vec![chain_id.into()];
+    let mut windows: Vec<String> = Vec::new();
+    for period in &periods {
+        let period_secs = PERIOD_MAP.get(period).copied().unwrap_or_default() as i64;
+        windows.push(format!(
+            "(period = ${} AND timestamp > ${})",
+            values.len() + 1,
+            values.len() + 2
+        ));
+        values.push((*period).into());
+        values.push((window_start - period_secs).into());
+    }
+
+    let rows = Rolling24hRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"SELECT token, period,
+                  (ARRAY_AGG(open ORDER BY timestamp ASC))[1] AS open,
+                  MAX(high) AS high,
+                  MIN(low) AS low,
+                  (ARRAY_AGG(close ORDER BY timestamp DESC))[1] AS close
+           FROM price_candle
+           WHERE chain_id = $1 AND ({})
+           GROUP BY token, period"#,
+            windows.join(" OR ")
+        ),
+        values,
+    ))
+    .all(db)
+    .await?;
+
+    let stats = REGISTRY
+        .listed_assets(chain_id)
+        .filter_map(|asset| {
+            let address = asset.address.as_ref()?;
+            let period = &REGISTRY.class_config(asset.asset_class).latest_period;
+            let row = rows
+                .iter()
+                .find(|row| &row.token == address && &row.period == period)?;
+            let change = row.close - row.open;
+            let change_percent = if row.open.is_zero() {
+                Decimal::ZERO
+            } else {
+                (change / row.open * Decimal::ONE_HUNDRED).round_dp(4)
+            };
+
+            Some(Price24HResponse {
+                _id: asset.symbol.clone(),
+                high: row.high,
+                low: row.low,
+                open: row.open,
+                close: row.close,
+                change,
+                change_percent,
+            })
+        })
+        .collect();
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::registryconfig::{AssetClass, AssetRecord};
+    use crate::utils::candlesutils::CandleBar;
+    use crate::utils::testdbutils::{clear_token, flat_bar, insert_bars, test_db};
+
+    // First listed asset of `asset_class` in the bundled registry.
+    fn listed_asset(chain_id: i64, asset_class: AssetClass) -> &'static AssetRecord {
+        REGISTRY
+            .listed_assets(chain_id)
+            .find(|asset| asset.asset_class == asset_class)
+            .expect("No listed asset of the class")
+    }
+
+    fn bar(timestamp: i64, open: &str, high: &str, low: &str, close: &str) -> CandleBar {
+        CandleBar {
+            timestamp,
+            open: open.parse().unwrap(),
+            high: high.parse().unwrap(),
+            low: low.parse().unwrap(),
+            close: close.parse().unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in TEST_DATABASE_URL"]
+    async fn window_opens_with_the_candle_covering_24h_ago() {
+        let db = test_db().await;
+        let chain_id = REGISTRY.networks["bitlayer_testnet"].chain_id;
+        let day = STATS_24H_WINDOW_SECS;
+        let today = 20_000 * day;
+        let now = today + 6 * 60 * 60;
+
+        // Daily candles: the day before yesterday is outside the window,
+        // yesterday's covers now - 24h and gives the open.
+        let realestate = listed_asset(chain_id, AssetClass::RealEstate);
+        clear_token(&db, chain_id, realestate.address.as_ref().unwrap()).await;
+        let daily = [
+            bar(today - 2 * day, "90", "95", "85", "100"),
+            bar(today - day, "100", "104", "99", "102"),
+            bar(today, "102", "110", "101", "108"),
+        ];
+        insert_bars(&db, chain_id, realestate.address.as_ref().unwrap(), "1d", &daily).await;
+
+        // Minute candles: the one starting just before now - 24h covers it.
+        let crypto = listed_asset(chain_id, AssetClass::Crypto);
+        clear_token(&db, chain_id, crypto.address.as_ref().unwrap()).await;
+        let minutes = [
+            flat_bar(now - day - 120, "1"),
+            flat_bar(now - day - 30, "2"),
+            flat_bar(now - 60, "3"),
+        ];
+        insert_bars(&db, chain_id, crypto.address.as_ref().unwrap(), "1m", &minutes).await;
+
+        let stats = rolling_24h_stats_at(&db, chain_id, now).await.unwrap();
+        let find = |symbol: &str| stats.iter().find(|stats| stats._id == symbol).unwrap();
+
+        let daily_stats = find(&realestate.symbol);
+        assert_eq!(daily_stats.open, "100".parse().unwrap());
+        assert_eq!(daily_stats.close, "108".parse().unwrap());
+        assert_eq!(daily_stats.high, "110".parse().unwrap());
+        assert_eq!(daily_stats.low, "99".parse().unwrap());
+        assert_eq!(daily_stats.change, "8".parse().unwrap());
+        assert_eq!(daily_stats.change_percent, "8".parse().unwrap());
+
+        let minute_stats = find(&crypto.symbol);
+        assert_eq!(minute_stats.open, "2".parse().unwrap());
+        assert_eq!(minute_stats.close, "3".parse().unwrap());
+        assert_eq!(minute_stats.low, "2".parse().unwrap());
+    }
+}
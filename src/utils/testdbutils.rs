@@ -0,0 +1,88 @@
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, Schema, Statement};
+use std::env;
+use tokio::sync::Mutex;
+
+use crate::entities::prelude::*;
+use crate::utils::candlesutils::{upsert_candle_bars, CandleBar};
+
+// Set once the price_candle table exists in the test database.
+static SCHEMA_READY: Mutex<bool> = Mutex::const_new(false);
+
+// Connects to the Postgres database in TEST_DATABASE_URL and creates the
+// price_candle table if needed. Database tests are #[ignore]d, so this only
+// runs under `cargo test -- --ignored`, where a missing database is an error.
+pub async fn test_db() -> DatabaseConnection {
+    let url = env::var("TEST_DATABASE_URL")
+        .expect("TEST_DATABASE_URL must be set to run the database tests");
+    let db = Database::connect(&url)
+        .await
+        .expect("Failed to connect to the test database");
+
+    let mut schema_ready = SCHEMA_READY.lock().await;
+    if !*schema_ready {
+        let create_table = Schema::new(DbBackend::Postgres)
+            .create_table_from_entity(PriceCandle)
+            .if_not_exists()
+            .to_owned();
+        db.execute(db.get_database_backend().build(&create_table))
+            .await
+            .expect("Failed to create price_candle");
+        db.execute(Statement::from_string(
+            DbBackend::Postgres,
+            r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_price_candle_unique
+               ON price_candle (chain_id, token, period, timestamp)"#,
+        ))
+        .await
+        .expect("Failed to create the price_candle unique index");
+        *schema_ready = true;
+    }
+
+    db
+}
+
+// Deletes every candle of `chain_id`. Each test uses its own chain id so tests
+// can run in parallel.
+pub async fn clear_chain(db: &DatabaseConnection, chain_id: i64) {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "DELETE FROM price_candle WHERE chain_id = $1",
+        [chain_id.into()],
+    ))
+    .await
+    .expect("Failed to clear test candles");
+}
+
+// Deletes the candles of one token, for tests that share a served chain.
+pub async fn clear_token(db: &DatabaseConnection, chain_id: i64, token: &str) {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "DELETE FROM price_candle WHERE chain_id = $1 AND token = $2",
+        [chain_id.into(), token.into()],
+    ))
+    .await
+    .expect("Failed to clear test candles");
+}
+
+// A bar with all four prices at `price`.
+pub fn flat_bar(timestamp: i64, price: &str) -> CandleBar {
+    let price = price.parse().expect("Invalid test price");
+    CandleBar {
+        timestamp,
+        open: price,
+        high: price,
+        low: price,
+        close: price,
+    }
+}
+
+pub async fn insert_bars(
+    db: &DatabaseConnection,
+    chain_id: i64,
+    token: &str,
+    period: &str,
+    bars: &[CandleBar],
+) {
+    upsert_candle_bars(db, chain_id, token, period, bars, "test")
+        .await
+        .expect("Failed to insert test candles");
+}